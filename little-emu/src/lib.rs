@@ -21,6 +21,7 @@ use std::io::{Read, Write};
 use little::*;
 use drawing::{*, RGB};
 use io::*;
+use dirty::DirtyBuffer;
 
 fn cstr(s: &str) -> CString {
 	CString::new(s).unwrap()
@@ -86,7 +87,7 @@ impl Buffer for TextureSurface {
 
 impl WriteBuffer for TextureSurface {
 	fn set_pixel(&mut self, x: i32, y: i32, color: RGB) {
-		if x < 0 || y < 0 || x >= self.width() || y >= self.width() {
+		if x < 0 || y < 0 || x >= self.width() || y >= self.height() {
 			panic!("Pixel ({}, {}) is out of bounds (width: {}, height: {})", x, y, self.width(), self.height());
 		}
 
@@ -96,10 +97,27 @@ impl WriteBuffer for TextureSurface {
 		self.pixels[pos+1] = color.1;
 		self.pixels[pos+2] = color.2;
 	}
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, color: RGB) {
+		let pos = ((y * self.w * 3) + (x * 3)) as usize;
+
+		for px in self.pixels[pos..pos + len as usize*3].chunks_mut(3) {
+			px.copy_from_slice(&[color.0, color.1, color.2]);
+		}
+	}
+
+	fn copy_row<P: ToPixel<RGB>>(&mut self, x: i32, y: i32, pixels: &[P]) {
+		let pos = ((y * self.w * 3) + (x * 3)) as usize;
+
+		for (px, color) in self.pixels[pos..pos + pixels.len()*3].chunks_mut(3).zip(pixels) {
+			let color = color.clone().to_pixel();
+			px.copy_from_slice(&[color.0, color.1, color.2]);
+		}
+	}
 }
 
 pub struct OpenGLPlatform {
-	surface: TextureSurface,
+	surface: DirtyBuffer<TextureSurface>,
 	//emulate touch state
 	touch_state: TouchInputState,
 	mouse_pos: Vector2,
@@ -134,7 +152,7 @@ const WIDTH: i32 = 128;
 const HEIGHT: i32 = 128;
 const ADDR: &str = "127.0.0.1:8085";
 
-impl Platform<TextureSurface> for OpenGLPlatform {
+impl Platform<DirtyBuffer<TextureSurface>> for OpenGLPlatform {
 	fn init() -> Self {
 		let scale_factor = 2.5;
 
@@ -230,10 +248,14 @@ void main()
 			TexParameteri(TEXTURE_2D, TEXTURE_MIN_FILTER, NEAREST as _);
 			TexParameteri(TEXTURE_2D, TEXTURE_MAG_FILTER, NEAREST as _);
 
+			let surface = TextureSurface::new(WIDTH, HEIGHT);
+			PixelStorei(UNPACK_ALIGNMENT, 1);
+			TexImage2D(TEXTURE_2D, 0, gl::RGB8 as _, surface.w, surface.h, 0, gl::RGB, UNSIGNED_BYTE, surface.pixels.as_ptr() as *const c_void);
+
 			let poll = mio::Poll::new().unwrap();
 
 			OpenGLPlatform {
-				surface: TextureSurface::new(WIDTH, HEIGHT),
+				surface: DirtyBuffer::new(surface),
 
 				touch_state: TouchInputState::new(10),
 				mouse_pos: vec2(0,0), mouse_down: false,
@@ -250,13 +272,27 @@ void main()
 		}
 	}
 
-	fn surface(&mut self) -> &mut TextureSurface {
+	fn surface(&mut self) -> &mut DirtyBuffer<TextureSurface> {
 		&mut self.surface
 	}
 
 	fn step(&mut self) -> bool {
 		unsafe {
-			TexImage2D(TEXTURE_2D, 0, gl::RGB8 as _, self.surface.width(), self.surface.height(), 0, gl::RGB, UNSIGNED_BYTE, self.surface.pixels.as_ptr() as *const c_void);
+			//only upload what changed since the last step
+			PixelStorei(UNPACK_ROW_LENGTH, self.surface.width());
+			for r in self.surface.regions().to_vec() {
+				PixelStorei(UNPACK_SKIP_PIXELS, r.from.x);
+				PixelStorei(UNPACK_SKIP_ROWS, r.from.y);
+
+				TexSubImage2D(TEXTURE_2D, 0, r.from.x, r.from.y, r.to.x - r.from.x, r.to.y - r.from.y, gl::RGB, UNSIGNED_BYTE, self.surface.draw.pixels.as_ptr() as *const c_void);
+			}
+
+			//back to the defaults so other uploads read their pixels tightly packed
+			PixelStorei(UNPACK_ROW_LENGTH, 0);
+			PixelStorei(UNPACK_SKIP_PIXELS, 0);
+			PixelStorei(UNPACK_SKIP_ROWS, 0);
+
+			self.surface.clear();
 			Uniform1i(self.tex_attrib, 0);
 
 			DrawArrays(TRIANGLES, 0, VERTS.len() as i32 / 2);
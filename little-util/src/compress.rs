@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use little::asset;
+use little::compressed::{TOKEN_LITERAL, TOKEN_RUN, TOKEN_COPY, MAX_TOKEN_PIXELS};
+
+/// A pixel in the layout it is stored in
+pub type Pixel = Vec<u8>;
+
+//a copy token and its offset
+const COPY_COST: usize = 5;
+//candidates checked for each copy, the most recent first
+const MAX_CHAIN: usize = 64;
+
+fn run_length(row: &[Pixel]) -> usize {
+	row.iter().take(MAX_TOKEN_PIXELS).take_while(|p| **p == row[0]).count()
+}
+
+fn tag(tag: u8, count: usize) -> u8 {
+	(tag << 6) | (count - 1) as u8
+}
+
+//literals and runs, calls token(offset, pixels) for each token written
+fn encode_plain<T: FnMut(usize, &[Pixel])>(row: &[Pixel], out: &mut Vec<u8>, mut token: T) {
+	let mut x = 0;
+
+	while x < row.len() {
+		let start = out.len();
+		let run = run_length(&row[x..]);
+
+		let len = if run >= 2 {
+			out.push(tag(TOKEN_RUN, run));
+			out.extend_from_slice(&row[x]);
+			run
+		} else {
+			let mut len = 1;
+			while x + len < row.len() && len < MAX_TOKEN_PIXELS && run_length(&row[x + len..]) < 2 {
+				len += 1;
+			}
+
+			out.push(tag(TOKEN_LITERAL, len));
+			for p in &row[x..x + len] {
+				out.extend_from_slice(p);
+			}
+
+			len
+		};
+
+		token(start, &row[x..x + len]);
+		x += len;
+	}
+}
+
+fn plain_cost(row: &[Pixel]) -> usize {
+	let mut out = Vec::new();
+	encode_plain(row, &mut out, |_, _| ());
+
+	out.len()
+}
+
+/// Encodes rows for little::compressed::StaticCompressedBuffer
+pub struct Encoder {
+	lz: bool,
+
+	pub data: Vec<u8>,
+	pub rows: Vec<u32>,
+
+	//pixels of every literal and run in order, None where a copy breaks them up
+	plain: Vec<Option<Pixel>>,
+	//offset and index into plain of each literal and run, by their first pixel
+	candidates: HashMap<Pixel, Vec<(usize, usize)>>
+}
+
+impl Encoder {
+	pub fn new(lz: bool) -> Self {
+		Encoder {
+			lz, data: Vec::new(), rows: Vec::new(),
+			plain: Vec::new(), candidates: HashMap::new()
+		}
+	}
+
+	fn emit_plain(&mut self, row: &[Pixel]) {
+		let (plain, candidates) = (&mut self.plain, &mut self.candidates);
+
+		encode_plain(row, &mut self.data, |offset, pixels| {
+			candidates.entry(pixels[0].clone()).or_insert_with(Vec::new).push((offset, plain.len()));
+			plain.extend(pixels.iter().cloned().map(Some));
+		});
+	}
+
+	//longest run of earlier pixels matching the start of row, and where it is
+	fn find(&self, row: &[Pixel]) -> Option<(usize, usize)> {
+		let candidates = self.candidates.get(&row[0])?;
+
+		candidates.iter().rev().take(MAX_CHAIN).map(|&(offset, i)| {
+			let len = row.iter().take(MAX_TOKEN_PIXELS).zip(&self.plain[i..])
+				.take_while(|&(a, b)| b.as_ref() == Some(a)).count();
+
+			(offset, len)
+		}).max_by_key(|&(_, len)| len)
+	}
+
+	pub fn row(&mut self, row: &[Pixel]) {
+		self.rows.push(self.data.len() as u32);
+
+		let (mut pending, mut x) = (0, 0);
+
+		while self.lz && x < row.len() {
+			match self.find(&row[x..]) {
+				Some((offset, len)) if COPY_COST < plain_cost(&row[x..x + len]) => {
+					self.emit_plain(&row[pending..x]);
+
+					self.data.push(tag(TOKEN_COPY, len));
+					self.data.extend_from_slice(&asset::u32_bytes(offset as u32));
+					self.plain.push(None);
+
+					x += len;
+					pending = x;
+				},
+				_ => x += 1
+			}
+		}
+
+		self.emit_plain(&row[pending..]);
+	}
+}
@@ -8,6 +8,8 @@ extern crate little;
 extern crate lodepng;
 
 mod error;
+mod compress;
+mod quantize;
 mod run;
 
 use std::io::{self, Write};
@@ -0,0 +1,80 @@
+pub type Color = [u8; 4];
+
+fn channel_range(colors: &[Color], c: usize) -> u8 {
+	let min = colors.iter().map(|x| x[c]).min().unwrap_or(0);
+	let max = colors.iter().map(|x| x[c]).max().unwrap_or(0);
+
+	max - min
+}
+
+//the channel a box of colors is most spread out along
+fn widest_channel(colors: &[Color]) -> (usize, u8) {
+	(0..4).map(|c| (c, channel_range(colors, c)))
+		.max_by_key(|&(_, range)| range).unwrap()
+}
+
+fn average(colors: &[Color]) -> Color {
+	let mut sum = [0u64; 4];
+
+	for x in colors {
+		for c in 0..4 {
+			sum[c] += x[c] as u64;
+		}
+	}
+
+	let n = colors.len() as u64;
+	[(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8, (sum[3] / n) as u8]
+}
+
+/// Picks up to `n` colors representing `pixels` with median cut
+pub fn palette(pixels: &[Color], n: usize) -> Vec<Color> {
+	let mut unique = pixels.to_vec();
+	unique.sort();
+	unique.dedup();
+
+	if unique.len() <= n {
+		return unique;
+	}
+
+	let mut boxes = vec![pixels.to_vec()];
+
+	while boxes.len() < n {
+		let (i, channel) = match boxes.iter().enumerate()
+			.map(|(i, b)| (i, widest_channel(b)))
+			.filter(|&(_, (_, range))| range > 0)
+			.max_by_key(|&(_, (_, range))| range) {
+			Some((i, (channel, _))) => (i, channel),
+			None => break
+		};
+
+		let mut b = boxes.swap_remove(i);
+		b.sort_by_key(|x| x[channel]);
+
+		let upper = b.split_off(b.len() / 2);
+		boxes.push(b);
+		boxes.push(upper);
+	}
+
+	boxes.iter().map(|b| average(b)).collect()
+}
+
+fn distance(a: &Color, b: &Color) -> u32 {
+	(0..4).map(|c| {
+		let d = a[c] as i32 - b[c] as i32;
+		(d * d) as u32
+	}).sum()
+}
+
+pub fn nearest(palette: &[Color], color: &Color) -> usize {
+	(0..palette.len()).min_by_key(|&i| distance(&palette[i], color)).unwrap()
+}
+
+/// Smallest of 1, 2, 4 or 8 bits that can index a palette of `len` colors
+pub fn index_bits(len: usize) -> u8 {
+	match len {
+		0...2 => 1,
+		3...4 => 2,
+		5...16 => 4,
+		_ => 8
+	}
+}
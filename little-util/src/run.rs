@@ -4,11 +4,22 @@ use error::{Result, ResultExt};
 use std::path;
 use std::fs;
 use std::io::{self, Write};
-use std::mem;
 
 use little::drawing::*;
+use little::asset::{self, AssetKind, AssetPixel};
+use quantize;
+use compress;
+
+fn asset_file(kind: AssetKind, format: u8, payload: Vec<u8>) -> Vec<u8> {
+	let mut buf = asset::header(kind, format, payload.len() as u32).to_vec();
+	buf.extend(payload);
+
+	buf
+}
+
+fn packfile(path: &path::Path, kind: AssetKind, format: u8, payload: Vec<u8>) -> Result<usize> {
+	let buf = asset_file(kind, format, payload);
 
-fn packfile(path: &path::Path, buf: Vec<u8>) -> Result<usize> {
 	let l = buf.len();
 	let path = path.with_extension("rc");
 	fs::write(&path, buf).chain_err(|| format!("Error writing to path {}", path.to_str().unwrap()))?;
@@ -16,6 +27,101 @@ fn packfile(path: &path::Path, buf: Vec<u8>) -> Result<usize> {
 	Ok(l)
 }
 
+fn pixel_bytes<P: AssetPixel>(p: P) -> Vec<u8> {
+	let mut buf = vec![0; P::SIZE];
+	p.write(&mut buf);
+
+	buf
+}
+
+fn color_bytes(px: quantize::Color, rgb: bool) -> Vec<u8> {
+	if rgb {
+		pixel_bytes(RGB(px[0], px[1], px[2]))
+	} else {
+		pixel_bytes(RGBA(px[0], px[1], px[2], px[3]))
+	}
+}
+
+//the payload of an image asset, with its kind and pixel format
+fn pack_image(pixels: &[quantize::Color], width: usize, height: usize, rgb: bool, colors: Option<usize>, compression: Option<&str>) -> Result<(AssetKind, u8, Vec<u8>)> {
+	let mut img = Vec::new();
+	img.extend_from_slice(&asset::i32_bytes(width as i32));
+	img.extend_from_slice(&asset::i32_bytes(height as i32));
+
+	let format = if rgb { RGB::TAG } else { RGBA::TAG };
+
+	if compression.is_some() && colors.is_some() {
+		bail!("Palettes can't be compressed");
+	}
+
+	//without alpha it mustn't split the palette or pick colors either
+	let pixels: Vec<quantize::Color> = pixels.iter().map(|&[r, g, b, a]| [r, g, b, if rgb { 255 } else { a }]).collect();
+	let pixels = &pixels[..];
+
+	let kind = if let Some(colors) = colors {
+		if colors < 1 || colors > 256 {
+			bail!("Palette size must be between 1 and 256");
+		}
+
+		println!("Quantizing...");
+		let palette = quantize::palette(pixels, colors);
+		let bits = quantize::index_bits(palette.len());
+
+		img.push(bits);
+		img.push(0);
+		img.extend_from_slice(&asset::u16_bytes(palette.len() as u16));
+
+		for &px in &palette {
+			img.extend(color_bytes(px, rgb));
+		}
+
+		let stride = (width * bits as usize + 7) / 8;
+		let mut indices = vec![0u8; stride * height];
+
+		for (i, px) in pixels.iter().enumerate() {
+			let (x, y) = (i % width, i / width);
+			let bit = x * bits as usize;
+
+			let index = quantize::nearest(&palette, px) as u8;
+			indices[y * stride + bit / 8] |= index << (8 - bits as usize - bit % 8);
+		}
+
+		img.append(&mut indices);
+		println!("{} colors, {} bits per pixel", palette.len(), bits);
+		AssetKind::Indexed
+	} else if let Some(compression) = compression {
+		let lz = match compression {
+			"rle" => false,
+			"lz" => true,
+			_ => bail!("Unknown compression {}, use rle or lz", compression)
+		};
+
+		let pixels: Vec<compress::Pixel> = pixels.iter().map(|&px| color_bytes(px, rgb)).collect();
+		let mut encoder = compress::Encoder::new(lz);
+
+		for row in pixels.chunks(width) {
+			encoder.row(row);
+		}
+
+		img.extend_from_slice(&[if lz { 2 } else { 1 }, 0, 0, 0]);
+
+		for row in encoder.rows {
+			img.extend_from_slice(&asset::u32_bytes(row));
+		}
+
+		img.append(&mut encoder.data);
+		AssetKind::Compressed
+	} else {
+		for &px in pixels {
+			img.extend(color_bytes(px, rgb));
+		}
+
+		AssetKind::Image
+	};
+
+	Ok((kind, format, img))
+}
+
 /// CLI Runtime
 pub fn run() -> Result<i32> {
 	let matches = App::new(env!("CARGO_PKG_NAME"))
@@ -30,7 +136,9 @@ pub fn run() -> Result<i32> {
 
 					.subcommand(SubCommand::with_name("pack-image")
 						.arg_from_usage("<PATH> 'Relative path to image'")
-						.arg_from_usage("--rgb 'Skip alpha channel'"))
+						.arg_from_usage("--rgb 'Skip alpha channel'")
+						.arg_from_usage("--palette [COLORS] 'Quantize to an indexed palette of up to 256 colors'")
+						.arg_from_usage("--compress [COMPRESSION] 'Compress rows with rle or lz'"))
 
 					.get_matches();
 
@@ -74,62 +182,50 @@ pub fn run() -> Result<i32> {
 					x_advance: glyph.advance().x as f32 / 64.0
 				};
 
-				unsafe {
-					buf.extend_from_slice(&mem::transmute::<char, [u8; 4]>(c));
-					buf.extend_from_slice(&mem::transmute::<u32, [u8; 4]>((bmp.buffer().len()) as u32));
+				buf.extend_from_slice(&asset::u32_bytes(c as u32));
+				buf.extend_from_slice(&asset::u32_bytes((header.width * header.height) as u32));
 
-					let header_bytes: [u8; mem::size_of::<FontCharHeader>()] = mem::transmute(header);
-					buf.extend_from_slice(&header_bytes);
+				for &field in &[header.width, header.height, header.left, header.top] {
+					buf.extend_from_slice(&asset::i32_bytes(field));
+				}
 
-					buf.extend_from_slice(bmp.buffer());
+				buf.extend_from_slice(&asset::f32_bytes(header.x_advance));
+
+				//rows can be padded past the width of the glyph
+				for row in bmp.buffer().chunks(bmp.pitch().max(1) as usize).take(header.height as usize) {
+					buf.extend_from_slice(&row[..header.width as usize]);
 				}
 			}
 
 			let mut final_buf = Vec::new();
+			final_buf.extend_from_slice(&asset::u32_bytes(pairs.len() as u32));
 
-			unsafe {
-				let pair_l: [u8; 4] = mem::transmute(pairs.len() as u32);
-				final_buf.extend_from_slice(&pair_l);
-
-				for pair in pairs {
-					let pair_bytes: [u8; mem::size_of::<FontCharKernPair>()] = mem::transmute(pair);
-					final_buf.extend_from_slice(&pair_bytes);
-				}
+			for (c1, c2, kerning) in pairs {
+				final_buf.extend_from_slice(&asset::u32_bytes(c1 as u32));
+				final_buf.extend_from_slice(&asset::u32_bytes(c2 as u32));
+				final_buf.extend_from_slice(&asset::f32_bytes(kerning));
 			}
 
 			final_buf.append(&mut buf);
 
-			println!("Finished! {} bytes", packfile(path, final_buf)?);
+			println!("Finished! {} bytes", packfile(path, AssetKind::Font, u8::TAG, final_buf)?);
 		},
 		("pack-image", Some(matches)) => {
 			println!("Reading...");
 			let path = path::Path::new(matches.value_of("PATH").unwrap());
-			let rgb = matches.is_present("--rgb");
+			let rgb = matches.is_present("rgb");
 			let png = lodepng::decode32_file(path)?;
 			
 			println!("Encoding...");
-			let mut img = Vec::new();
-
-			unsafe {
-				let width: [u8; 4] = mem::transmute(png.width as i32);
-				let height: [u8; 4] = mem::transmute(png.height as i32);
-
-				img.extend_from_slice(&width);
-				img.extend_from_slice(&height);
-
-				for px in png.buffer {
-					if rgb {
-						let buf: [u8; mem::size_of::<RGB>()] = mem::transmute(RGB(px.r, px.g, px.b));
-						img.extend_from_slice(&buf);
-					} else {
-						let buf: [u8; mem::size_of::<RGBA>()] = mem::transmute(RGBA(px.r, px.g, px.b, px.a));
-						img.extend_from_slice(&buf);
-					}
-				}
-			}
+			let pixels: Vec<quantize::Color> = png.buffer.iter().map(|px| [px.r, px.g, px.b, px.a]).collect();
 
-			packfile(path, img)?;
-			println!("Finished!");
+			let colors = match matches.value_of("palette") {
+				Some(colors) => Some(colors.parse().chain_err(|| "Palette size must be a number")?),
+				None => None
+			};
+
+			let (kind, format, img) = pack_image(&pixels, png.width, png.height, rgb, colors, matches.value_of("compress"))?;
+			println!("Finished! {} bytes", packfile(path, kind, format, img)?);
 		},
 		_ => {
 			writeln!(io::stderr(), "Subcommand not supplied! See usage:")?;
@@ -138,4 +234,74 @@ pub fn run() -> Result<i32> {
 	}
 
 	Ok(0)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use little::vec2;
+	use little::compressed::SliceCompressedBuffer;
+
+	#[test]
+	fn palette_packs_an_indexed_image() {
+		let colors = [[255, 0, 0, 255], [0, 0, 255, 255], [0, 255, 0, 128]];
+		let pixels: Vec<quantize::Color> = (0..15).map(|i| colors[i % 3]).collect();
+
+		let (kind, format, payload) = pack_image(&pixels, 5, 3, false, Some(4), None).unwrap();
+		assert_eq!(kind, AssetKind::Indexed);
+
+		let buf = asset_file(kind, format, payload);
+		let img = SliceIndexedBuffer::<RGBA>::load(&buf).unwrap();
+
+		assert_eq!((img.width(), img.height(), img.bits(), img.palette_len()), (5, 3, 2, 3));
+
+		for (i, px) in pixels.iter().enumerate() {
+			assert_eq!(img.get_pixel(i as i32 % 5, i as i32 / 5), RGBA(px[0], px[1], px[2], px[3]));
+		}
+	}
+
+	#[test]
+	fn compression_round_trips() {
+		//runs, literals and rows repeating earlier ones for lz to copy
+		let pixels: Vec<quantize::Color> = (0..40*6).map(|i| {
+			let (x, y) = (i % 40, i / 40);
+			if x < 12 { [1, 2, 3, 255] } else { [(x * 7 % 5) as u8, (y % 2) as u8 * 100, 9, (x * 13) as u8] }
+		}).collect();
+
+		for &compression in &["rle", "lz"] {
+			let (kind, format, payload) = pack_image(&pixels, 40, 6, false, None, Some(compression)).unwrap();
+			assert_eq!(kind, AssetKind::Compressed);
+
+			let buf = asset_file(kind, format, payload);
+			let img = SliceCompressedBuffer::<RGBA>::load(&buf).unwrap();
+
+			for (i, px) in pixels.iter().enumerate() {
+				assert_eq!(img.get_pixel(i as i32 % 40, i as i32 / 40), RGBA(px[0], px[1], px[2], px[3]));
+			}
+
+			//reading backwards along a row starts over instead of using the last position
+			assert_eq!(img.get_pixel(3, 5), RGBA(1, 2, 3, 255));
+
+			//blits are cut to the target and blend what has alpha
+			let mut target: FrameBuffer<RGBA, 16, 4> = FrameBuffer::new(RGBA(0, 0, 0, 255));
+			img.blit(&mut target, vec2(-30, -1));
+
+			assert_eq!(target.get_pixel(0, 0), img.get_pixel(30, 1).choose(RGBA(0, 0, 0, 255), 30.0 * 13.0 % 256.0 / 255.0));
+			assert_eq!(target.get_pixel(10, 3), RGBA(0, 0, 0, 255));
+		}
+
+		assert!(pack_image(&pixels, 40, 6, false, Some(4), Some("lz")).is_err());
+	}
+
+	#[test]
+	fn rgb_palettes_ignore_alpha() {
+		let pixels = [[10, 20, 30, 255], [10, 20, 30, 0], [200, 20, 30, 255], [200, 20, 30, 10]];
+
+		let (kind, format, payload) = pack_image(&pixels, 2, 2, true, Some(2), None).unwrap();
+		let buf = asset_file(kind, format, payload);
+		let img = SliceIndexedBuffer::<RGB>::load(&buf).unwrap();
+
+		assert_eq!(img.palette_len(), 2);
+		assert_eq!((img.get_pixel(1, 0), img.get_pixel(1, 1)), (RGB(10, 20, 30), RGB(200, 20, 30)));
+	}
+}
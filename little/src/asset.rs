@@ -0,0 +1,267 @@
+use super::*;
+use drawing::{Pixel, RGB, RGBA, RGB565};
+
+//every asset starts with
+//0..4 magic, 4 version, 5 kind, 6 pixel format tag, 7 unused, 8..12 length of the rest
+//all fields after that are little endian
+pub const MAGIC: [u8; 4] = *b"LTRC";
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssetKind {
+	//width i32, height i32, pixels
+	Image = 0,
+	//width i32, height i32, bits per index u8, unused u8, palette length u16, palette, indices
+	Indexed = 1,
+	//width i32, height i32, compression u8, 3 unused, row offsets u32 for each row, data
+	Compressed = 2,
+	//kerning pair count u32, pairs of (char u32, char u32, kerning f32),
+	//then for each glyph char u32, pixel count u32, width, height, left, top i32, x advance f32, pixels
+	Font = 3,
+	//see display_list
+	DisplayList = 4
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssetError {
+	//shorter than its header says
+	TooShort,
+	BadMagic,
+	UnsupportedVersion(u8),
+	WrongKind(u8),
+	WrongFormat(u8),
+	//a field that doesn't fit the rest of the asset
+	Corrupt
+}
+
+//how a pixel format is stored in assets
+pub trait AssetPixel: Pixel {
+	const TAG: u8;
+	const SIZE: usize;
+
+	fn read(b: &[u8]) -> Self;
+	fn write(&self, b: &mut [u8]);
+
+	//the pixels stored in b when they are laid out in memory just like that, so rows can be borrowed
+	fn cast(_b: &[u8]) -> Option<&[Self]> {
+		None
+	}
+}
+
+impl AssetPixel for u8 {
+	const TAG: u8 = 0;
+	const SIZE: usize = 1;
+
+	fn read(b: &[u8]) -> Self {
+		b[0]
+	}
+
+	fn write(&self, b: &mut [u8]) {
+		b[0] = *self;
+	}
+
+	fn cast(b: &[u8]) -> Option<&[Self]> {
+		Some(b)
+	}
+}
+
+impl AssetPixel for RGB {
+	const TAG: u8 = 1;
+	const SIZE: usize = 3;
+
+	fn read(b: &[u8]) -> Self {
+		RGB(b[0], b[1], b[2])
+	}
+
+	fn write(&self, b: &mut [u8]) {
+		b[0] = self.0; b[1] = self.1; b[2] = self.2;
+	}
+
+	fn cast(b: &[u8]) -> Option<&[Self]> {
+		//three bytes in order with nothing in between, see the repr on RGB
+		Some(unsafe { core::slice::from_raw_parts(b.as_ptr() as *const RGB, b.len() / 3) })
+	}
+}
+
+impl AssetPixel for RGBA {
+	const TAG: u8 = 2;
+	const SIZE: usize = 4;
+
+	fn read(b: &[u8]) -> Self {
+		RGBA(b[0], b[1], b[2], b[3])
+	}
+
+	fn write(&self, b: &mut [u8]) {
+		b[0] = self.0; b[1] = self.1; b[2] = self.2; b[3] = self.3;
+	}
+
+	fn cast(b: &[u8]) -> Option<&[Self]> {
+		Some(unsafe { core::slice::from_raw_parts(b.as_ptr() as *const RGBA, b.len() / 4) })
+	}
+}
+
+impl AssetPixel for RGB565 {
+	const TAG: u8 = 3;
+	const SIZE: usize = 2;
+
+	fn read(b: &[u8]) -> Self {
+		RGB565(read_u16(b))
+	}
+
+	fn write(&self, b: &mut [u8]) {
+		b[..2].copy_from_slice(&u16_bytes(self.0));
+	}
+
+	//only little endian platforms store a u16 like assets do, and only when b is aligned for it
+	fn cast(b: &[u8]) -> Option<&[Self]> {
+		if cfg!(target_endian = "big") {
+			return None;
+		}
+
+		match unsafe { b.align_to::<RGB565>() } {
+			(&[], pixels, _) => Some(pixels),
+			_ => None
+		}
+	}
+}
+
+pub fn read_u16(b: &[u8]) -> u16 {
+	b[0] as u16 | (b[1] as u16) << 8
+}
+
+pub fn read_u32(b: &[u8]) -> u32 {
+	b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24
+}
+
+pub fn read_i32(b: &[u8]) -> i32 {
+	read_u32(b) as i32
+}
+
+pub fn read_f32(b: &[u8]) -> f32 {
+	f32::from_bits(read_u32(b))
+}
+
+pub fn u16_bytes(x: u16) -> [u8; 2] {
+	[x as u8, (x >> 8) as u8]
+}
+
+pub fn u32_bytes(x: u32) -> [u8; 4] {
+	[x as u8, (x >> 8) as u8, (x >> 16) as u8, (x >> 24) as u8]
+}
+
+pub fn i32_bytes(x: i32) -> [u8; 4] {
+	u32_bytes(x as u32)
+}
+
+pub fn f32_bytes(x: f32) -> [u8; 4] {
+	u32_bytes(x.to_bits())
+}
+
+pub fn header(kind: AssetKind, format: u8, len: u32) -> [u8; HEADER_LEN] {
+	let len = u32_bytes(len);
+
+	[MAGIC[0], MAGIC[1], MAGIC[2], MAGIC[3], VERSION, kind as u8, format, 0,
+		len[0], len[1], len[2], len[3]]
+}
+
+//checks the header and returns what comes after it
+pub fn payload(buf: &[u8], kind: AssetKind, format: u8) -> Result<&[u8], AssetError> {
+	if buf.len() < HEADER_LEN {
+		return Err(AssetError::TooShort);
+	} else if buf[0..4] != MAGIC {
+		return Err(AssetError::BadMagic);
+	} else if buf[4] != VERSION {
+		return Err(AssetError::UnsupportedVersion(buf[4]));
+	} else if buf[5] != kind as u8 {
+		return Err(AssetError::WrongKind(buf[5]));
+	} else if buf[6] != format {
+		return Err(AssetError::WrongFormat(buf[6]));
+	}
+
+	let len = read_u32(&buf[8..12]) as usize;
+	buf[HEADER_LEN..].get(..len).ok_or(AssetError::TooShort)
+}
+
+//width and height at the start of image payloads
+pub fn size(payload: &[u8]) -> Result<(i32, i32), AssetError> {
+	if payload.len() < 8 {
+		return Err(AssetError::TooShort);
+	}
+
+	let (width, height) = (read_i32(&payload[0..4]), read_i32(&payload[4..8]));
+	if width < 0 || height < 0 {
+		return Err(AssetError::Corrupt);
+	}
+
+	Ok((width, height))
+}
+
+//bytes taken by width by height things of size bytes each
+pub fn area(width: i32, height: i32, size: usize) -> Result<usize, AssetError> {
+	(width as usize).checked_mul(height as usize).and_then(|n| n.checked_mul(size)).ok_or(AssetError::Corrupt)
+}
+
+//the bytes at pos..pos+len, if they are there
+pub fn slice(b: &[u8], pos: usize, len: usize) -> Result<&[u8], AssetError> {
+	pos.checked_add(len).and_then(|end| b.get(pos..end)).ok_or(AssetError::TooShort)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use drawing::{Buffer, SliceBuffer};
+
+	fn image(payload: &[u8]) -> [u8; 64] {
+		let mut buf = [0; 64];
+		buf[..HEADER_LEN].copy_from_slice(&header(AssetKind::Image, RGB565::TAG, payload.len() as u32));
+		buf[HEADER_LEN..HEADER_LEN + payload.len()].copy_from_slice(payload);
+
+		buf
+	}
+
+	#[test]
+	fn header_layout() {
+		assert_eq!(header(AssetKind::Compressed, RGB::TAG, 0x01020304), [b'L', b'T', b'R', b'C', VERSION, 2, 1, 0, 4, 3, 2, 1]);
+	}
+
+	#[test]
+	fn headers_are_checked() {
+		let buf = image(&[1, 0, 0, 0, 1, 0, 0, 0, 0x34, 0x12]);
+		let len = HEADER_LEN + 10;
+
+		let img = SliceBuffer::<RGB565>::load(&buf[..len]).unwrap();
+		assert_eq!(img.get_pixel(0, 0), RGB565(0x1234));
+
+		assert_eq!(payload(&buf[..len], AssetKind::Image, RGB565::TAG), Ok(&buf[HEADER_LEN..len]));
+		assert_eq!(payload(&buf[..HEADER_LEN - 1], AssetKind::Image, RGB565::TAG), Err(AssetError::TooShort));
+		assert_eq!(payload(&buf[..len - 1], AssetKind::Image, RGB565::TAG), Err(AssetError::TooShort));
+		assert_eq!(payload(&buf, AssetKind::Font, RGB565::TAG), Err(AssetError::WrongKind(0)));
+		assert_eq!(payload(&buf, AssetKind::Image, RGB::TAG), Err(AssetError::WrongFormat(3)));
+
+		let mut bad = buf;
+		bad[0] = b'X';
+		assert_eq!(payload(&bad, AssetKind::Image, RGB565::TAG), Err(AssetError::BadMagic));
+
+		let mut bad = buf;
+		bad[4] = VERSION + 1;
+		assert_eq!(payload(&bad, AssetKind::Image, RGB565::TAG), Err(AssetError::UnsupportedVersion(VERSION + 1)));
+	}
+
+	#[test]
+	fn sizes_are_checked() {
+		//more pixels than the payload holds
+		assert_eq!(SliceBuffer::<RGB565>::load(&image(&[2, 0, 0, 0, 1, 0, 0, 0, 0, 0])).err(), Some(AssetError::TooShort));
+		assert_eq!(SliceBuffer::<RGB565>::load(&image(&[0xff, 0xff, 0xff, 0xff, 1, 0, 0, 0])).err(), Some(AssetError::Corrupt));
+		assert_eq!(area(i32::MAX, i32::MAX, usize::MAX), Err(AssetError::Corrupt));
+		assert_eq!(slice(&[0; 4], usize::MAX, 2), Err(AssetError::TooShort));
+	}
+
+	#[test]
+	fn fields_are_little_endian() {
+		assert_eq!(read_u16(&u16_bytes(0xbeef)), 0xbeef);
+		assert_eq!(u32_bytes(0x12345678), [0x78, 0x56, 0x34, 0x12]);
+		assert_eq!(read_i32(&i32_bytes(-5)), -5);
+		assert_eq!(read_f32(&f32_bytes(1.5)), 1.5);
+	}
+}
@@ -0,0 +1,314 @@
+use super::*;
+use drawing::{Pixel, ToPixel, Buffer, WriteBuffer, BlendMode};
+
+//how many states can be saved at once
+pub const MAX_SAVED: usize = 8;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CanvasState {
+	//where the local origin is on the target
+	pub translate: Vector2,
+	//how many target pixels across each local pixel is
+	pub scale: i32,
+	//in target pixels
+	pub clip: Region,
+	pub alpha: f32
+}
+
+//draws onto draw in local coordinates, with a stack of states to save and restore around nested widgets
+//everything drawn through it is moved, scaled, clipped and faded by the current state
+pub struct Canvas<'a, B> {
+	pub draw: &'a mut B,
+
+	state: CanvasState,
+	saved: [CanvasState; MAX_SAVED],
+	len: usize
+}
+
+fn div_floor(a: i32, b: i32) -> i32 {
+	a.div_euclid(b)
+}
+
+fn div_ceil(a: i32, b: i32) -> i32 {
+	-(-a).div_euclid(b)
+}
+
+impl<'a, B: WriteBuffer> Canvas<'a, B> {
+	pub fn new(draw: &'a mut B) -> Self {
+		let state = CanvasState {translate: vec2(0, 0), scale: 1, clip: draw.clip(), alpha: 1.0};
+		Canvas {draw, state, saved: [state; MAX_SAVED], len: 0}
+	}
+
+	pub fn state(&self) -> &CanvasState {
+		&self.state
+	}
+
+	//returns false when too many states are saved already
+	pub fn save(&mut self) -> bool {
+		if self.len == MAX_SAVED {
+			return false;
+		}
+
+		self.saved[self.len] = self.state;
+		self.len += 1;
+
+		true
+	}
+
+	//goes back to the last saved state, returns false if there is none
+	pub fn restore(&mut self) -> bool {
+		if self.len == 0 {
+			return false;
+		}
+
+		self.len -= 1;
+		self.state = self.saved[self.len];
+
+		true
+	}
+
+	//moves the origin by in local coordinates
+	pub fn translate(&mut self, by: Vector2) {
+		self.state.translate = self.state.translate + by*self.state.scale;
+	}
+
+	pub fn scale(&mut self, scale: i32) {
+		self.state.scale *= scale.max(1);
+	}
+
+	//narrows the clip to from..to in local coordinates, it can only shrink until restored
+	pub fn clip_rect(&mut self, from: Vector2, to: Vector2) {
+		let (t, s) = (self.state.translate, self.state.scale);
+		self.state.clip = self.state.clip.intersect(Region::new(t + from*s, t + to*s));
+	}
+
+	//multiplies the alpha everything is drawn with
+	pub fn alpha(&mut self, alpha: f32) {
+		self.state.alpha *= alpha.max(0.0).min(1.0);
+	}
+
+	fn target(&self, x: i32, y: i32) -> Vector2 {
+		self.state.translate + vec2(x, y)*self.state.scale
+	}
+
+	//a span moved onto draw and cut to the clip, with how many pixels were cut from its start
+	//only when it maps pixel for pixel, otherwise spans go through set_pixel
+	fn target_span(&self, x: i32, y: i32, len: i32) -> Option<(i32, i32, i32, usize)> {
+		if self.state.scale != 1 || self.state.alpha < 1.0 {
+			return None;
+		}
+
+		let (p, clip) = (self.target(x, y), self.state.clip);
+		let (from, to) = (p.x.max(clip.from.x), (p.x + len).min(clip.to.x));
+
+		if p.y >= clip.from.y && p.y < clip.to.y && from < to {
+			Some((from, p.y, to - from, (from - p.x) as usize))
+		} else {
+			Some((0, 0, 0, 0))
+		}
+	}
+}
+
+impl<'a, B: WriteBuffer> Buffer for Canvas<'a, B> {
+	type Format = B::Format;
+
+	//the local size reaching the right and bottom edges of draw
+	fn width(&self) -> i32 {
+		div_ceil(self.draw.width() - self.state.translate.x, self.state.scale).max(0)
+	}
+
+	fn height(&self) -> i32 {
+		div_ceil(self.draw.height() - self.state.translate.y, self.state.scale).max(0)
+	}
+
+	//pixels outside the clip read from its nearest edge, or from the nearest edge of draw when nothing is left of the clip
+	fn get_pixel(&self, x: i32, y: i32) -> B::Format {
+		let clip = if self.state.clip.is_empty() {
+			Region::new(vec2(0, 0), vec2(self.draw.width(), self.draw.height()))
+		} else {
+			self.state.clip
+		};
+		let p = self.target(x, y);
+
+		self.draw.get_pixel(p.x.min(clip.to.x - 1).max(clip.from.x), p.y.min(clip.to.y - 1).max(clip.from.y))
+	}
+}
+
+impl<'a, B: WriteBuffer> WriteBuffer for Canvas<'a, B> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: B::Format) {
+		let (from, s) = (self.target(x, y), self.state.scale);
+		let r = self.state.clip.intersect(Region::new(from, from + s));
+
+		for y in r.from.y..r.to.y {
+			for x in r.from.x..r.to.x {
+				let px = if self.state.alpha < 1.0 {
+					p.clone().choose(self.draw.get_pixel(x, y), self.state.alpha)
+				} else {
+					p.clone()
+				};
+
+				self.draw.set_pixel(x, y, px);
+			}
+		}
+	}
+
+	fn blend_mode(&self) -> BlendMode {
+		self.draw.blend_mode()
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: B::Format) {
+		match self.target_span(x, y, len) {
+			Some((x, y, len, _)) => if len > 0 {
+				self.draw.fill_span(x, y, len, p);
+			},
+			None => for x in x..x+len {
+				self.set_pixel(x, y, p.clone());
+			}
+		}
+	}
+
+	fn blend_span(&mut self, x: i32, y: i32, len: i32, p: B::Format, alpha: f32) {
+		match self.target_span(x, y, len) {
+			Some((x, y, len, _)) => if len > 0 {
+				self.draw.blend_span(x, y, len, p, alpha);
+			},
+			None => {
+				let mode = self.blend_mode();
+
+				for x in x..x+len {
+					let dst = self.get_pixel(x, y);
+					self.set_pixel(x, y, mode.apply(p.clone(), alpha, dst));
+				}
+			}
+		}
+	}
+
+	fn copy_row<P: ToPixel<B::Format>>(&mut self, x: i32, y: i32, pixels: &[P]) {
+		match self.target_span(x, y, pixels.len() as i32) {
+			Some((x, y, len, skip)) => if len > 0 {
+				self.draw.copy_row(x, y, &pixels[skip..skip + len as usize]);
+			},
+			None => for (i, p) in pixels.iter().enumerate() {
+				self.set_pixel(x + i as i32, y, p.clone().to_pixel());
+			}
+		}
+	}
+
+	//every local pixel that covers part of the clip
+	fn clip(&self) -> Region {
+		let clip = self.state.clip.intersect(self.draw.clip());
+		if clip.is_empty() {
+			return Region::new(vec2(0, 0), vec2(0, 0));
+		}
+
+		let (t, s) = (self.state.translate, self.state.scale);
+		Region::new(vec2(div_floor(clip.from.x - t.x, s), div_floor(clip.from.y - t.y, s)),
+			vec2(div_ceil(clip.to.x - t.x, s), div_ceil(clip.to.y - t.y, s)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use drawing::{FrameBuffer, RGB};
+
+	const BLACK: RGB = RGB(0, 0, 0);
+	const RED: RGB = RGB(255, 0, 0);
+
+	#[test]
+	fn states_move_scale_and_clip() {
+		let mut frame: FrameBuffer<RGB, 12, 10> = FrameBuffer::new(BLACK);
+
+		{
+			let mut c = Canvas::new(&mut frame);
+			c.translate(vec2(2, 1));
+			c.scale(2);
+			c.set_pixel(1, 1, RED);
+
+			//local pixels are 2 wide from (2, 1), so 0..2 ends at 6
+			c.clip_rect(vec2(0, 0), vec2(2, 2));
+			assert_eq!(c.clip(), Region::new(vec2(0, 0), vec2(2, 2)));
+			assert_eq!(c.state().clip, Region::new(vec2(2, 1), vec2(6, 5)));
+
+			c.fill_span(-1, 0, 6, RGB(0, 255, 0));
+		}
+
+		for y in 0..10 {
+			for x in 0..12 {
+				let expected = if (2..6).contains(&x) && (1..3).contains(&y) {
+					RGB(0, 255, 0)
+				} else if (4..6).contains(&x) && (3..5).contains(&y) {
+					RED
+				} else {
+					BLACK
+				};
+
+				assert_eq!((x, y, frame.get_pixel(x, y)), (x, y, expected));
+			}
+		}
+	}
+
+	#[test]
+	fn alpha_fades_what_is_drawn() {
+		let mut frame: FrameBuffer<RGB, 4, 4> = FrameBuffer::new(BLACK);
+
+		{
+			let mut c = Canvas::new(&mut frame);
+			c.alpha(0.5);
+			c.alpha(0.5);
+			c.alpha(2.0);
+			assert_eq!(c.state().alpha, 0.25);
+
+			c.set_pixel(1, 1, RED);
+			c.fill_span(0, 2, 4, RED);
+		}
+
+		let faded = RED.choose(BLACK, 0.25);
+		assert_eq!(frame.get_pixel(1, 1), faded);
+		assert_eq!(frame.get_pixel(0, 1), BLACK);
+		assert!(frame.pixels[2].iter().all(|&p| p == faded));
+	}
+
+	#[test]
+	fn restore_undoes_nested_saves() {
+		let mut frame: FrameBuffer<RGB, 12, 10> = FrameBuffer::new(BLACK);
+		let mut c = Canvas::new(&mut frame);
+
+		assert!(c.save());
+		c.translate(vec2(3, 2));
+		assert!(c.save());
+		c.scale(3);
+		c.clip_rect(vec2(0, 0), vec2(1, 1));
+		c.alpha(0.5);
+		c.translate(vec2(1, 1));
+		assert_eq!(c.state().translate, vec2(6, 5));
+
+		assert!(c.restore());
+		let s = *c.state();
+		assert_eq!((s.translate, s.scale, s.clip, s.alpha), (vec2(3, 2), 1, Region::new(vec2(0, 0), vec2(12, 10)), 1.0));
+
+		assert!(c.restore());
+		assert_eq!(c.state().translate, vec2(0, 0));
+		assert!(!c.restore());
+
+		for _ in 0..MAX_SAVED {
+			assert!(c.save());
+		}
+		assert!(!c.save());
+	}
+
+	#[test]
+	fn empty_clip_reads_stay_on_the_target() {
+		let mut frame: FrameBuffer<RGB, 12, 10> = FrameBuffer::new(BLACK);
+		frame.pixels[9][11] = RED;
+
+		let mut c = Canvas::new(&mut frame);
+		c.clip_rect(vec2(20, 20), vec2(30, 30));
+		assert!(c.clip().is_empty());
+
+		//nothing is drawn, and reads come from the nearest pixel of the target
+		c.set_pixel(0, 0, RED);
+		assert_eq!(c.get_pixel(0, 0), BLACK);
+		assert_eq!(c.get_pixel(25, 25), RED);
+	}
+}
@@ -0,0 +1,221 @@
+use super::*;
+use drawing::{Buffer, WriteBuffer, BlendMode};
+use asset::{self, AssetPixel, AssetKind, AssetError};
+use core::marker::PhantomData;
+use core::cell::Cell;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+	//runs and literals
+	Rle,
+	//runs and literals, plus copies of pixels earlier in the data
+	Lz
+}
+
+//an image compressed row by row, where any row can be decoded on its own, see asset for the layout
+//rows are tokens of a tag in the top two bits and the pixel count - 1 in the rest, then
+//literal: that many pixels
+//run: one pixel repeated
+//copy: where in the data the pixels are as u32, which is always a literal or run followed by more of them
+pub struct SliceCompressedBuffer<'a, Format: AssetPixel> {
+	width: i32,
+	height: i32,
+	compression: Compression,
+	rows: &'a [u8],
+	data: &'a [u8],
+	//row, x and position of the last token get_pixel read, so reading along a row picks up from there
+	cursor: Cell<(i32, i32, usize)>,
+	format: PhantomData<Format>
+}
+
+pub type StaticCompressedBuffer<Format> = SliceCompressedBuffer<'static, Format>;
+
+pub const TOKEN_LITERAL: u8 = 0;
+pub const TOKEN_RUN: u8 = 1;
+pub const TOKEN_COPY: u8 = 2;
+//most pixels a single token holds
+pub const MAX_TOKEN_PIXELS: usize = 64;
+
+impl<'a, F: AssetPixel> SliceCompressedBuffer<'a, F> {
+	//walks every row so a corrupt image is caught here instead of while drawing
+	pub fn load(buf: &'a [u8]) -> Result<Self, AssetError> {
+		let payload = asset::payload(buf, AssetKind::Compressed, F::TAG)?;
+		let (width, height) = asset::size(payload)?;
+
+		let compression = match asset::slice(payload, 8, 4)?[0] {
+			1 => Compression::Rle,
+			2 => Compression::Lz,
+			_ => return Err(AssetError::Corrupt)
+		};
+
+		let rows = asset::slice(payload, 12, asset::area(height, 4, 1)?)?;
+		let img = SliceCompressedBuffer {
+			width, height, compression, rows,
+			data: &payload[12 + rows.len()..],
+			cursor: Cell::new((-1, 0, 0)),
+			format: PhantomData
+		};
+
+		for y in 0..height {
+			img.spans(img.row_start(y), |_, _, _, _, _| true)?;
+		}
+
+		Ok(img)
+	}
+
+	pub fn compression(&self) -> Compression {
+		self.compression
+	}
+
+	fn pixel(&self, pos: usize) -> F {
+		F::read(&self.data[pos..])
+	}
+
+	//the tag, pixel count, where its pixels start and where the next token starts
+	fn token(&self, pos: usize) -> Result<(u8, i32, usize, usize), AssetError> {
+		let byte = *self.data.get(pos).ok_or(AssetError::TooShort)?;
+		let (tag, count) = (byte >> 6, (byte & 0x3f) as i32 + 1);
+
+		let len = match tag {
+			TOKEN_LITERAL => count as usize*F::SIZE,
+			TOKEN_RUN => F::SIZE,
+			TOKEN_COPY if self.compression == Compression::Lz => 4,
+			_ => return Err(AssetError::Corrupt)
+		};
+
+		asset::slice(self.data, pos + 1, len)?;
+		Ok((tag, count, pos + 1, pos + 1 + len))
+	}
+
+	//x and position of the first token of row y
+	fn row_start(&self, y: i32) -> (i32, usize) {
+		(0, asset::read_u32(&self.rows[4*y as usize..]) as usize)
+	}
+
+	//calls span(x, length, where the pixels are, whether it is a run, the token it is in) along a row until it returns false
+	//from is the x and position of a token in the row to start at, see row_start
+	fn spans<S: FnMut(i32, i32, usize, bool, (i32, usize)) -> bool>(&self, from: (i32, usize), mut span: S) -> Result<(), AssetError> {
+		let (mut x, mut pos) = from;
+
+		while x < self.width {
+			let token = (x, pos);
+			let (tag, count, start, next) = self.token(pos)?;
+			pos = next;
+
+			if tag != TOKEN_COPY {
+				if !span(x, count, start, tag == TOKEN_RUN, token) {
+					return Ok(());
+				}
+			} else {
+				let mut from = asset::read_u32(&self.data[start..]) as usize;
+				let mut copied = 0;
+
+				while copied < count {
+					let (tag, len, start, next) = self.token(from)?;
+					let len = len.min(count - copied);
+
+					if tag == TOKEN_COPY {
+						return Err(AssetError::Corrupt);
+					} else if !span(x + copied, len, start, tag == TOKEN_RUN, token) {
+						return Ok(());
+					}
+
+					copied += len;
+					from = next;
+				}
+			}
+
+			x += count;
+		}
+
+		//tokens running past the end of the row
+		if x != self.width {
+			return Err(AssetError::Corrupt);
+		}
+
+		Ok(())
+	}
+
+	//calls f(x, length, pixel) for every run of the same pixel in row y
+	pub fn decode_row<R: FnMut(i32, i32, F)>(&self, y: i32, mut f: R) {
+		let _ = self.spans(self.row_start(y), |x, len, pos, run, _| {
+			if run {
+				f(x, len, self.pixel(pos));
+			} else {
+				for i in 0..len {
+					f(x + i, 1, self.pixel(pos + i as usize*F::SIZE));
+				}
+			}
+
+			true
+		});
+	}
+
+	//draws the image onto target at pos a row at a time, cut to the target's clip
+	//runs that just replace what is under them are filled as spans, anything else blends with the target's mode
+	pub fn blit<B: WriteBuffer<Format=F>>(&self, target: &mut B, pos: Vector2) {
+		let clip = target.clip().intersect(Region::new(pos, pos + vec2(self.width, self.height)));
+		let mode = target.blend_mode();
+
+		for ty in clip.from.y..clip.to.y {
+			self.decode_row(ty - pos.y, |x, len, p| {
+				let (from, to) = ((pos.x + x).max(clip.from.x), (pos.x + x + len).min(clip.to.x));
+
+				if from >= to {
+					return;
+				}
+
+				if mode == BlendMode::Replace || (mode == BlendMode::SrcOver && !p.soft()) {
+					target.fill_span(from, ty, to - from, p);
+				} else {
+					let alpha = p.alpha();
+					target.blend_span(from, ty, to - from, p, alpha);
+				}
+			});
+		}
+	}
+}
+
+impl<'a, F: AssetPixel> Buffer for SliceCompressedBuffer<'a, F> {
+	type Format = F;
+
+	fn width(&self) -> i32 {
+		self.width
+	}
+
+	fn height(&self) -> i32 {
+		self.height
+	}
+
+	//walks the row up to x, or from the last pixel read when that is further along the same row
+	//so reading a row left to right only decodes it once
+	fn get_pixel(&self, x: i32, y: i32) -> F {
+		assert!(x >= 0 && x < self.width && y >= 0 && y < self.height, "pixel ({}, {}) is outside the image", x, y);
+
+		let (cy, cx, cpos) = self.cursor.get();
+		let from = if cy == y && cx <= x { (cx, cpos) } else { self.row_start(y) };
+
+		let mut pixel = None;
+
+		let _ = self.spans(from, |start, len, pos, run, (tx, tpos)| {
+			if x < start + len {
+				pixel = Some(self.pixel(if run { pos } else { pos + (x - start) as usize*F::SIZE }));
+				self.cursor.set((y, tx, tpos));
+
+				false
+			} else {
+				true
+			}
+		});
+
+		//load walked every row, so any pixel inside the image decodes
+		pixel.expect("rows are checked by load")
+	}
+}
+
+#[macro_export]
+macro_rules! include_compressed_buffer {
+	($format: path, $path: tt) => {
+		little::compressed::StaticCompressedBuffer::<$format>::load(include_bytes!($path))
+	};
+}
@@ -0,0 +1,219 @@
+use super::*;
+use drawing::{ToPixel, Buffer, WriteBuffer, BlendMode};
+
+//regions tracked at once, past that new ones are merged into the closest
+pub const MAX_DIRTY: usize = 8;
+
+//remembers which parts of draw changed since the last clear, so only those need to be sent to the display
+pub struct DirtyBuffer<B> {
+	pub draw: B,
+
+	regions: [Region; MAX_DIRTY],
+	len: usize
+}
+
+//overlapping or right next to each other
+fn touching(a: &Region, b: &Region) -> bool {
+	a.from.x <= b.to.x && b.from.x <= a.to.x && a.from.y <= b.to.y && b.from.y <= a.to.y
+}
+
+impl<B> DirtyBuffer<B> {
+	pub fn new(draw: B) -> Self {
+		DirtyBuffer {
+			draw,
+			regions: [Region::new(vec2(0, 0), vec2(0, 0)); MAX_DIRTY],
+			len: 0
+		}
+	}
+
+	pub fn is_dirty(&self) -> bool {
+		self.len > 0
+	}
+
+	//call after the regions are on the display
+	pub fn clear(&mut self) {
+		self.len = 0;
+	}
+}
+
+impl<B: Buffer> DirtyBuffer<B> {
+	//only what is on draw is kept, so the regions can be sent as they are
+	pub fn mark(&mut self, region: Region) {
+		let region = region.intersect(Region::new(vec2(0, 0), vec2(self.draw.width(), self.draw.height())));
+
+		if region.is_empty() || self.regions[..self.len].iter().any(|r| r.contains(&region)) {
+			return;
+		}
+
+		if let Some(r) = self.regions[..self.len].iter_mut().find(|r| touching(r, &region)) {
+			*r = r.union(region);
+			return;
+		}
+
+		//regions grown since they were added might overlap now
+		if self.len == MAX_DIRTY {
+			self.coalesce();
+		}
+
+		if self.len < MAX_DIRTY {
+			self.regions[self.len] = region;
+			self.len += 1;
+		} else {
+			//grow whichever region it adds the least area to
+			let r = self.regions.iter_mut()
+				.min_by_key(|r| r.union(region).area() - r.area()).expect("MAX_DIRTY is more than 0");
+
+			*r = r.union(region);
+		}
+	}
+
+	fn coalesce(&mut self) {
+		let mut i = 0;
+
+		while i < self.len {
+			let mut merged = false;
+
+			for j in i+1..self.len {
+				if touching(&self.regions[i], &self.regions[j]) {
+					self.regions[i] = self.regions[i].union(self.regions[j]);
+					self.regions[j] = self.regions[self.len - 1];
+					self.len -= 1;
+
+					merged = true;
+					break;
+				}
+			}
+
+			//a grown region can touch ones before it again
+			if merged {
+				i = 0;
+			} else {
+				i += 1;
+			}
+		}
+	}
+
+	//the changed regions, merged so none of them overlap
+	pub fn regions(&mut self) -> &[Region] {
+		self.coalesce();
+		&self.regions[..self.len]
+	}
+}
+
+impl<B: Buffer> Buffer for DirtyBuffer<B> {
+	type Format = B::Format;
+
+	fn width(&self) -> i32 {
+		self.draw.width()
+	}
+
+	fn height(&self) -> i32 {
+		self.draw.height()
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> B::Format {
+		self.draw.get_pixel(x, y)
+	}
+
+	fn get_row(&self, y: i32) -> Option<&[B::Format]> {
+		self.draw.get_row(y)
+	}
+}
+
+impl<B: WriteBuffer> WriteBuffer for DirtyBuffer<B> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: B::Format) {
+		self.draw.set_pixel(x, y, p);
+		self.mark(Region::new(vec2(x, y), vec2(x + 1, y + 1)));
+	}
+
+	fn blend_mode(&self) -> BlendMode {
+		self.draw.blend_mode()
+	}
+
+	fn clip(&self) -> Region {
+		self.draw.clip()
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: B::Format) {
+		self.draw.fill_span(x, y, len, p);
+		self.mark(Region::new(vec2(x, y), vec2(x + len, y + 1)));
+	}
+
+	fn blend_span(&mut self, x: i32, y: i32, len: i32, p: B::Format, alpha: f32) {
+		self.draw.blend_span(x, y, len, p, alpha);
+		self.mark(Region::new(vec2(x, y), vec2(x + len, y + 1)));
+	}
+
+	fn copy_row<P: ToPixel<B::Format>>(&mut self, x: i32, y: i32, pixels: &[P]) {
+		self.draw.copy_row(x, y, pixels);
+		self.mark(Region::new(vec2(x, y), vec2(x + pixels.len() as i32, y + 1)));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use drawing::FrameBuffer;
+
+	fn region(x: i32, y: i32, w: i32, h: i32) -> Region {
+		Region::new(vec2(x, y), vec2(x + w, y + h))
+	}
+
+	fn dirty() -> DirtyBuffer<FrameBuffer<u8, 64, 32>> {
+		DirtyBuffer::new(FrameBuffer::new(0))
+	}
+
+	#[test]
+	fn regions_are_cut_to_the_buffer() {
+		let mut d = dirty();
+
+		d.mark(region(-4, -3, 10, 8));
+		d.mark(region(60, 30, 10, 10));
+		d.mark(region(-20, 0, 10, 10));
+
+		assert_eq!(d.regions(), &[region(0, 0, 6, 5), region(60, 30, 4, 2)]);
+	}
+
+	#[test]
+	fn touching_regions_merge() {
+		let mut d = dirty();
+
+		d.mark(region(0, 0, 4, 4));
+		d.mark(region(4, 0, 4, 4));
+		d.mark(region(20, 20, 2, 2));
+
+		assert_eq!(d.regions(), &[region(0, 0, 8, 4), region(20, 20, 2, 2)]);
+
+		//anything already inside a region changes nothing
+		d.mark(region(1, 1, 2, 2));
+		assert_eq!(d.regions(), &[region(0, 0, 8, 4), region(20, 20, 2, 2)]);
+
+		d.clear();
+		assert!(!d.is_dirty());
+		assert!(d.regions().is_empty());
+	}
+
+	#[test]
+	fn past_max_dirty_regions_are_merged() {
+		let mut d = dirty();
+
+		for i in 0..MAX_DIRTY as i32 {
+			d.mark(region(i * 5, 0, 2, 2));
+		}
+
+		//grows the first until it touches the second, which is only merged once there is no room
+		d.mark(region(1, 0, 5, 1));
+		d.mark(region(0, 20, 2, 2));
+
+		let r = d.regions().to_vec();
+		assert_eq!(r.len(), MAX_DIRTY);
+		assert!(r.contains(&region(0, 0, 7, 2)) && r.contains(&region(0, 20, 2, 2)));
+
+		//with nothing to merge, whichever region grows the least takes it
+		d.mark(region(30, 20, 2, 2));
+
+		let r = d.regions();
+		assert_eq!(r.len(), MAX_DIRTY);
+		assert!(r.contains(&region(30, 0, 2, 22)));
+	}
+}
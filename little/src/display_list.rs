@@ -0,0 +1,844 @@
+use super::*;
+use drawing::*;
+use path::{Path, PathCommand, PathView, StrokeStyle, LineCap, LineJoin};
+use raster::{self, FillRule, RasterError};
+use dirty::DirtyBuffer;
+use asset::{self, AssetPixel, AssetKind, AssetError};
+use core::str;
+
+//where a text command's text is in its list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextRef {
+	start: u16,
+	len: u16
+}
+
+//where a path command's path is in its list, with the box around its points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathRef {
+	start: u16,
+	len: u16,
+	tolerance: f32,
+	from: Vector2,
+	to: Vector2
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command<P> {
+	Line {from: Vector2, to: Vector2, style: StrokeStyle, color: P},
+	Arc {from: Vector2, to: Vector2, start: f32, end: f32, thickness: f32, round_caps: bool, color: P},
+	Rect {from: Vector2, to: Vector2, roundness: i32, color: P},
+	RectStroke {from: Vector2, to: Vector2, roundness: i32, thickness: f32, color: P},
+	Ellipse {from: Vector2, to: Vector2, sector: Option<(f32, f32)>, color: P},
+	EllipseStroke {from: Vector2, to: Vector2, sector: Option<(f32, f32)>, thickness: f32, color: P},
+	Circle {origin: Vector2, radius: i32, color: P},
+	CircleStroke {origin: Vector2, radius: i32, thickness: f32, color: P},
+	Triangle {points: [Vector2; 3], color: P},
+	TriangleStroke {points: [Vector2; 3], thickness: f32, color: P},
+	//copies one of the images given to replay
+	Blit {image: u8, from: Vector2, to: Vector2, filter: Filter},
+	//text kept in the list, drawn with one of the fonts given to replay, push it with push_text
+	Text {font: u8, text: TextRef, from: Vector2, to: Vector2, size: f32, color: P},
+	//paths kept in the list, push them with push_path, push_stroke_path, push_poly or push_polyline
+	FillPath {path: PathRef, rule: FillRule, color: P},
+	StrokePath {path: PathRef, style: StrokeStyle, color: P},
+	//like copy_transform, size is the size of the image so the list knows what it covers
+	CopyTransform {image: u8, size: Vector2, pos: Vector2, scale: Vector2f, origin: Vector2, angle: f32, skew: Vector2f, filter: Filter}
+}
+
+//the box around points, grown by margin
+fn around(points: &[Vector2], margin: i32) -> Region {
+	let mut r = Region::new(points[0], points[0] + 1);
+	for &p in points {
+		r = r.union(Region::new(p, p + 1));
+	}
+
+	Region::new(r.from - margin, r.to + margin)
+}
+
+fn margin(thickness: f32) -> i32 {
+	ceil(thickness) as i32 + 1
+}
+
+impl<P: Copy> Command<P> {
+	//all the pixels the command can change
+	pub fn bounds(&self) -> Region {
+		match *self {
+			Command::Line {from, to, style, ..} => around(&[from, to], margin(style.width)),
+			Command::Arc {from, to, thickness, ..} => around(&[from, to], margin(thickness)),
+			Command::Rect {from, to, ..} | Command::Blit {from, to, ..} => Region::new(from, to),
+			Command::RectStroke {from, to, ..} | Command::Ellipse {from, to, ..} => around(&[from, to], 1),
+			//the mitered corners of a sector stick out a little
+			Command::EllipseStroke {from, to, thickness, ..} => around(&[from, to], margin(thickness)),
+			Command::Circle {origin, radius, ..} | Command::CircleStroke {origin, radius, ..} => around(&[origin], radius + 1),
+			Command::Triangle {points, ..} => around(&points, 1),
+			Command::TriangleStroke {points, thickness, ..} => around(&points, margin(thickness)),
+			//glyphs sit above the line and the last one on a line can go past to
+			Command::Text {from, to, size, ..} => {
+				let m = ceil(DEFAULT_LINE_HEIGHT * size) as i32;
+				Region::new(from - m, to + m)
+			},
+			Command::FillPath {path, ..} => around(&[path.from, path.to], 1),
+			//miter joins reach furthest out
+			Command::StrokePath {path, style, ..} => around(&[path.from, path.to], margin(style.width * style.miter_limit.max(1.0))),
+			Command::CopyTransform {size, pos, scale, origin, angle, skew, ..} => transform_bounds(pos, scale, origin, angle, skew, size)
+		}
+	}
+}
+
+const TAG_LINE: u8 = 0;
+const TAG_ARC: u8 = 1;
+const TAG_RECT: u8 = 2;
+const TAG_RECT_STROKE: u8 = 3;
+const TAG_ELLIPSE: u8 = 4;
+const TAG_ELLIPSE_STROKE: u8 = 5;
+const TAG_CIRCLE: u8 = 6;
+const TAG_CIRCLE_STROKE: u8 = 7;
+const TAG_TRIANGLE: u8 = 8;
+const TAG_TRIANGLE_STROKE: u8 = 9;
+const TAG_BLIT: u8 = 10;
+const TAG_TEXT: u8 = 11;
+const TAG_FILL_PATH: u8 = 12;
+const TAG_STROKE_PATH: u8 = 13;
+const TAG_COPY_TRANSFORM: u8 = 14;
+
+const PATH_MOVE: u8 = 0;
+const PATH_LINE: u8 = 1;
+const PATH_QUAD: u8 = 2;
+const PATH_CUBIC: u8 = 3;
+const PATH_CLOSE: u8 = 4;
+
+//the box around every point of a path, curves never leave the box around their controls
+fn path_box(commands: &[PathCommand]) -> (Vector2, Vector2) {
+	let (mut min, mut max) = (vec2f(0.0, 0.0), vec2f(0.0, 0.0));
+	let mut first = true;
+
+	let mut add = |p: Vector2f| {
+		if first {
+			min = p;
+			max = p;
+			first = false;
+		}
+
+		min = vec2f(min.x.min(p.x), min.y.min(p.y));
+		max = vec2f(max.x.max(p.x), max.y.max(p.y));
+	};
+
+	for cmd in commands {
+		match *cmd {
+			PathCommand::MoveTo(p) | PathCommand::LineTo(p) => add(p),
+			PathCommand::QuadTo(c, p) => { add(c); add(p); },
+			PathCommand::CubicTo(c1, c2, p) => { add(c1); add(c2); add(p); },
+			PathCommand::Close => ()
+		}
+	}
+
+	(vec2(floor(min.x) as i32, floor(min.y) as i32), vec2(ceil(max.x) as i32, ceil(max.y) as i32))
+}
+
+struct Writer<'a> {
+	buf: &'a mut [u8],
+	pos: usize,
+	full: bool
+}
+
+impl<'a> Writer<'a> {
+	fn bytes(&mut self, b: &[u8]) {
+		match self.buf.get_mut(self.pos..self.pos + b.len()) {
+			Some(dst) => dst.copy_from_slice(b),
+			None => self.full = true
+		}
+
+		self.pos += b.len();
+	}
+
+	fn u8(&mut self, x: u8) {
+		self.bytes(&[x]);
+	}
+
+	fn i32(&mut self, x: i32) {
+		self.bytes(&asset::i32_bytes(x));
+	}
+
+	fn f32(&mut self, x: f32) {
+		self.bytes(&asset::f32_bytes(x));
+	}
+
+	fn vec2(&mut self, v: Vector2) {
+		self.i32(v.x);
+		self.i32(v.y);
+	}
+
+	fn pixel<P: AssetPixel>(&mut self, p: P) {
+		let mut b = [0; 8];
+		p.write(&mut b);
+		self.bytes(&b[..P::SIZE]);
+	}
+
+	fn style(&mut self, style: StrokeStyle) {
+		self.f32(style.width);
+		self.u8(style.cap as u8);
+		self.u8(style.join as u8);
+		self.f32(style.miter_limit);
+	}
+
+	fn vec2f(&mut self, v: Vector2f) {
+		self.f32(v.x);
+		self.f32(v.y);
+	}
+
+	fn path_ref(&mut self, path: PathRef) {
+		self.bytes(&asset::u16_bytes(path.start));
+		self.bytes(&asset::u16_bytes(path.len));
+		self.f32(path.tolerance);
+	}
+
+	fn path_command(&mut self, cmd: PathCommand) {
+		match cmd {
+			PathCommand::MoveTo(p) => { self.u8(PATH_MOVE); self.vec2f(p); },
+			PathCommand::LineTo(p) => { self.u8(PATH_LINE); self.vec2f(p); },
+			PathCommand::QuadTo(c, p) => { self.u8(PATH_QUAD); self.vec2f(c); self.vec2f(p); },
+			PathCommand::CubicTo(c1, c2, p) => { self.u8(PATH_CUBIC); self.vec2f(c1); self.vec2f(c2); self.vec2f(p); },
+			PathCommand::Close => self.u8(PATH_CLOSE)
+		}
+	}
+
+	fn sector(&mut self, sector: Option<(f32, f32)>) {
+		let (start, end) = sector.unwrap_or((0.0, 0.0));
+
+		self.u8(sector.is_some() as u8);
+		self.f32(start);
+		self.f32(end);
+	}
+}
+
+struct Reader<'a> {
+	buf: &'a [u8],
+	pos: usize
+}
+
+impl<'a> Reader<'a> {
+	fn bytes(&mut self, len: usize) -> Result<&'a [u8], AssetError> {
+		let b = asset::slice(self.buf, self.pos, len)?;
+		self.pos += len;
+
+		Ok(b)
+	}
+
+	fn u8(&mut self) -> Result<u8, AssetError> {
+		Ok(self.bytes(1)?[0])
+	}
+
+	fn bool(&mut self) -> Result<bool, AssetError> {
+		match self.u8()? {
+			0 => Ok(false),
+			1 => Ok(true),
+			_ => Err(AssetError::Corrupt)
+		}
+	}
+
+	fn u16(&mut self) -> Result<u16, AssetError> {
+		Ok(asset::read_u16(self.bytes(2)?))
+	}
+
+	fn i32(&mut self) -> Result<i32, AssetError> {
+		Ok(asset::read_i32(self.bytes(4)?))
+	}
+
+	fn f32(&mut self) -> Result<f32, AssetError> {
+		Ok(asset::read_f32(self.bytes(4)?))
+	}
+
+	fn vec2(&mut self) -> Result<Vector2, AssetError> {
+		Ok(vec2(self.i32()?, self.i32()?))
+	}
+
+	fn points(&mut self) -> Result<[Vector2; 3], AssetError> {
+		Ok([self.vec2()?, self.vec2()?, self.vec2()?])
+	}
+
+	fn pixel<P: AssetPixel>(&mut self) -> Result<P, AssetError> {
+		Ok(P::read(self.bytes(P::SIZE)?))
+	}
+
+	fn style(&mut self) -> Result<StrokeStyle, AssetError> {
+		let width = self.f32()?;
+
+		let cap = match self.u8()? {
+			0 => LineCap::Butt,
+			1 => LineCap::Round,
+			2 => LineCap::Square,
+			_ => return Err(AssetError::Corrupt)
+		};
+
+		let join = match self.u8()? {
+			0 => LineJoin::Miter,
+			1 => LineJoin::Round,
+			2 => LineJoin::Bevel,
+			_ => return Err(AssetError::Corrupt)
+		};
+
+		Ok(StrokeStyle::new(width).cap(cap).join(join).miter_limit(self.f32()?))
+	}
+
+	fn filter(&mut self) -> Result<Filter, AssetError> {
+		match self.u8()? {
+			0 => Ok(Filter::Nearest),
+			1 => Ok(Filter::Bilinear),
+			_ => Err(AssetError::Corrupt)
+		}
+	}
+
+	fn vec2f(&mut self) -> Result<Vector2f, AssetError> {
+		Ok(vec2f(self.f32()?, self.f32()?))
+	}
+
+	fn rule(&mut self) -> Result<FillRule, AssetError> {
+		match self.u8()? {
+			0 => Ok(FillRule::NonZero),
+			1 => Ok(FillRule::EvenOdd),
+			_ => Err(AssetError::Corrupt)
+		}
+	}
+
+	fn path_command(&mut self) -> Result<PathCommand, AssetError> {
+		Ok(match self.u8()? {
+			PATH_MOVE => PathCommand::MoveTo(self.vec2f()?),
+			PATH_LINE => PathCommand::LineTo(self.vec2f()?),
+			PATH_QUAD => PathCommand::QuadTo(self.vec2f()?, self.vec2f()?),
+			PATH_CUBIC => PathCommand::CubicTo(self.vec2f()?, self.vec2f()?, self.vec2f()?),
+			PATH_CLOSE => PathCommand::Close,
+			_ => return Err(AssetError::Corrupt)
+		})
+	}
+
+	fn sector(&mut self) -> Result<Option<(f32, f32)>, AssetError> {
+		let some = self.bool()?;
+		let sector = (self.f32()?, self.f32()?);
+
+		Ok(if some { Some(sector) } else { None })
+	}
+}
+
+//a frame recorded as commands, which can be replayed onto any buffer, compared with the last frame or stored
+//holds up to N commands, TEXT bytes of text and PATHS path commands for paths, polygons and polylines
+pub struct DisplayList<P: Copy, const N: usize, const TEXT: usize, const PATHS: usize> {
+	commands: [Option<Command<P>>; N],
+	len: usize,
+
+	text: [u8; TEXT],
+	text_len: usize,
+
+	paths: [PathCommand; PATHS],
+	paths_len: usize
+}
+
+impl<P: Pixel + Copy + PartialEq, const N: usize, const TEXT: usize, const PATHS: usize> DisplayList<P, N, TEXT, PATHS> {
+	pub const fn new() -> Self {
+		DisplayList {
+			commands: [None; N], len: 0,
+			text: [0; TEXT], text_len: 0,
+			paths: [PathCommand::Close; PATHS], paths_len: 0
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.len = 0;
+		self.text_len = 0;
+		self.paths_len = 0;
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn get(&self, i: usize) -> Option<&Command<P>> {
+		self.commands[..self.len].get(i).and_then(|c| c.as_ref())
+	}
+
+	pub fn text(&self, text: TextRef) -> &str {
+		let start = text.start as usize;
+		str::from_utf8(&self.text[start..start + text.len as usize]).unwrap_or("")
+	}
+
+	pub fn path(&self, path: PathRef) -> PathView {
+		let start = path.start as usize;
+		PathView {commands: &self.paths[start..start + path.len as usize], tolerance: path.tolerance}
+	}
+
+	//returns false when the list is full
+	pub fn push(&mut self, cmd: Command<P>) -> bool {
+		if self.len == N {
+			return false;
+		}
+
+		self.commands[self.len] = Some(cmd);
+		self.len += 1;
+
+		true
+	}
+
+	//returns false when there is no room for the command or the text
+	pub fn push_text(&mut self, font: u8, txt: &str, from: Vector2, to: Vector2, size: f32, color: P) -> bool {
+		let start = self.text_len;
+		if self.len == N || TEXT - start < txt.len() || start + txt.len() > u16::max_value() as usize {
+			return false;
+		}
+
+		self.text[start..start + txt.len()].copy_from_slice(txt.as_bytes());
+		self.text_len += txt.len();
+
+		let text = TextRef {start: start as u16, len: txt.len() as u16};
+		self.push(Command::Text {font, text, from, to, size, color})
+	}
+
+	//keeps commands in the list, None when there is no room for them or another command
+	//so a command can always be pushed after it returns Some, and nothing is left in the pool otherwise
+	fn push_commands<I: Iterator<Item=PathCommand>>(&mut self, commands: I, tolerance: f32) -> Option<PathRef> {
+		let start = self.paths_len;
+		if self.len == N {
+			return None;
+		}
+
+		for cmd in commands {
+			if self.paths_len == PATHS || self.paths_len >= u16::max_value() as usize {
+				self.paths_len = start;
+				return None;
+			}
+
+			self.paths[self.paths_len] = cmd;
+			self.paths_len += 1;
+		}
+
+		let (from, to) = path_box(&self.paths[start..self.paths_len]);
+		Some(PathRef {start: start as u16, len: (self.paths_len - start) as u16, tolerance, from, to})
+	}
+
+	//returns false when there is no room for the command or the path, or the path is full itself
+	//a full path is refused before anything of it goes in the pool
+	pub fn push_path<const M: usize>(&mut self, path: &Path<M>, rule: FillRule, color: P) -> bool {
+		if path.is_full() {
+			return false;
+		}
+
+		match self.push_commands(path.commands().iter().cloned(), path.tolerance) {
+			Some(path) => self.push(Command::FillPath {path, rule, color}),
+			None => false
+		}
+	}
+
+	pub fn push_stroke_path<const M: usize>(&mut self, path: &Path<M>, style: StrokeStyle, color: P) -> bool {
+		if path.is_full() {
+			return false;
+		}
+
+		match self.push_commands(path.commands().iter().cloned(), path.tolerance) {
+			Some(path) => self.push(Command::StrokePath {path, style, color}),
+			None => false
+		}
+	}
+
+	//like poly, kept as a path through the same points so it draws the same
+	pub fn push_poly(&mut self, points: &[Vector2], rule: FillRule, color: P) -> bool {
+		let commands = points.iter().enumerate().map(|(i, &p)| if i == 0 {
+			PathCommand::MoveTo(p.into())
+		} else {
+			PathCommand::LineTo(p.into())
+		});
+
+		match self.push_commands(commands, path::DEFAULT_TOLERANCE) {
+			Some(path) => self.push(Command::FillPath {path, rule, color}),
+			None => false
+		}
+	}
+
+	//like polyline, kept as an open path
+	pub fn push_polyline(&mut self, points: &[Vector2], style: StrokeStyle, color: P) -> bool {
+		let commands = points.iter().enumerate().map(|(i, &p)| if i == 0 {
+			PathCommand::MoveTo(p.into())
+		} else {
+			PathCommand::LineTo(p.into())
+		});
+
+		match self.push_commands(commands, path::DEFAULT_TOLERANCE) {
+			Some(path) => self.push(Command::StrokePath {path, style, color}),
+			None => false
+		}
+	}
+
+	//draws every command onto target, blits and text pick their image and font by index from images and fonts
+	//paths with too many edges through a pixel are left out like fill_path does, and the first such error is returned
+	pub fn replay<B, I, F>(&self, target: &mut B, images: &[&I], fonts: &[&F]) -> Result<(), RasterError>
+		where B: WriteBuffer, P: ToPixel<B::Format>, u8: ToPixel<P>, I: Buffer<Format=P>, F: FontBuffer {
+		let mut result = Ok(());
+
+		for cmd in self.commands[..self.len].iter().filter_map(|c| c.as_ref()) {
+			match *cmd {
+				Command::Line {from, to, style, color} => target.line(from, to, &color, &style),
+				Command::Arc {from, to, start, end, thickness, round_caps, color} =>
+					target.arc(from, to, start, end, &color, thickness, round_caps),
+				Command::Rect {from, to, roundness, color} => target.rect(from, to, &color, roundness),
+				Command::RectStroke {from, to, roundness, thickness, color} =>
+					target.rect_stroke(from, to, &color, roundness, thickness),
+				Command::Ellipse {from, to, sector, color} => target.ellipse(from, to, sector, &color),
+				Command::EllipseStroke {from, to, sector, thickness, color} =>
+					target.ellipse_stroke(from, to, sector, &color, thickness),
+				Command::Circle {origin, radius, color} => target.circle(origin, radius, &color),
+				Command::CircleStroke {origin, radius, thickness, color} =>
+					target.circle_stroke(origin, radius, &color, thickness),
+				Command::Triangle {points, color} => target.triangle(points, &color),
+				Command::TriangleStroke {points, thickness, color} => target.triangle_stroke(points, &color, thickness),
+				Command::Blit {image, from, to, filter} => if let Some(image) = images.get(image as usize) {
+					target.copy(from, to, *image, filter);
+				},
+				Command::Text {font, text, from, to, size, color} => if let Some(font) = fonts.get(font as usize) {
+					target.text(&DrawText::new(*font, self.text(text)).font_size(size), from, to, &color);
+				},
+				Command::FillPath {path, rule, color} => {
+					let filled = fill(target, &self.path(path), rule, &color);
+					result = result.and(filled);
+				},
+				Command::StrokePath {path, style, color} => {
+					let filled = fill(target, &self.path(path).stroke(style), FillRule::NonZero, &color);
+					result = result.and(filled);
+				},
+				Command::CopyTransform {image, pos, scale, origin, angle, skew, filter, ..} => if let Some(image) = images.get(image as usize) {
+					target.copy_transform(pos, scale, origin, angle, skew, *image, filter);
+				}
+			}
+		}
+
+		result
+	}
+
+	fn same_path(&self, a: PathRef, prev: &Self, b: PathRef) -> bool {
+		a.tolerance == b.tolerance && self.path(a).commands == prev.path(b).commands
+	}
+
+	fn same(&self, a: &Command<P>, prev: &Self, b: &Command<P>) -> bool {
+		match (*a, *b) {
+			(Command::Text {font, text, from, to, size, color},
+				Command::Text {font: font2, text: text2, from: from2, to: to2, size: size2, color: color2}) =>
+				(font, from, to, size, color) == (font2, from2, to2, size2, color2) && self.text(text) == prev.text(text2),
+			(Command::FillPath {path, rule, color}, Command::FillPath {path: path2, rule: rule2, color: color2}) =>
+				(rule, color) == (rule2, color2) && self.same_path(path, prev, path2),
+			(Command::StrokePath {path, style, color}, Command::StrokePath {path: path2, style: style2, color: color2}) =>
+				(style, color) == (style2, color2) && self.same_path(path, prev, path2),
+			(a, b) => a == b
+		}
+	}
+
+	//marks where this frame draws differently from prev on dirty, comparing commands in order
+	//blits only compare which image and where, not what is in it
+	pub fn diff<B: Buffer>(&self, prev: &Self, dirty: &mut DirtyBuffer<B>) {
+		for i in 0..self.len.max(prev.len) {
+			let (a, b) = (self.get(i), prev.get(i));
+
+			if let (Some(a), Some(b)) = (a, b) {
+				if self.same(a, prev, b) {
+					continue;
+				}
+			}
+
+			for cmd in a.iter().chain(b.iter()) {
+				dirty.mark(cmd.bounds());
+			}
+		}
+	}
+}
+
+//stored as an asset of its own, the payload is
+//command count u16, text length u16, text, path command count u16, path commands,
+//then each command as a tag u8 and its fields in order
+//vectors are two i32, flags, filters and fill rules u8, styles are width f32, cap u8, join u8, miter limit f32
+//sectors are a flag and two f32, text is start u16 and length u16, paths are start u16, length u16 and tolerance f32
+//path commands are a u8 for move, line, quad, cubic or close and their points as two f32 each
+impl<P: AssetPixel + Copy + PartialEq, const N: usize, const TEXT: usize, const PATHS: usize> DisplayList<P, N, TEXT, PATHS> {
+	//writes the list to out, returns how many bytes it took or None if out is too small
+	//or the list holds more than a u16 can count
+	pub fn serialize(&self, out: &mut [u8]) -> Option<usize> {
+		let max = u16::max_value() as usize;
+		if self.len > max || self.text_len > max || self.paths_len > max {
+			return None;
+		}
+
+		let mut w = Writer {buf: out, pos: asset::HEADER_LEN, full: false};
+
+		w.bytes(&asset::u16_bytes(self.len as u16));
+		w.bytes(&asset::u16_bytes(self.text_len as u16));
+		w.bytes(&self.text[..self.text_len]);
+
+		w.bytes(&asset::u16_bytes(self.paths_len as u16));
+		for &cmd in &self.paths[..self.paths_len] {
+			w.path_command(cmd);
+		}
+
+		for cmd in self.commands[..self.len].iter().filter_map(|c| c.as_ref()) {
+			match *cmd {
+				Command::Line {from, to, style, color} => {
+					w.u8(TAG_LINE); w.vec2(from); w.vec2(to); w.style(style); w.pixel(color);
+				},
+				Command::Arc {from, to, start, end, thickness, round_caps, color} => {
+					w.u8(TAG_ARC); w.vec2(from); w.vec2(to);
+					w.f32(start); w.f32(end); w.f32(thickness); w.u8(round_caps as u8); w.pixel(color);
+				},
+				Command::Rect {from, to, roundness, color} => {
+					w.u8(TAG_RECT); w.vec2(from); w.vec2(to); w.i32(roundness); w.pixel(color);
+				},
+				Command::RectStroke {from, to, roundness, thickness, color} => {
+					w.u8(TAG_RECT_STROKE); w.vec2(from); w.vec2(to); w.i32(roundness); w.f32(thickness); w.pixel(color);
+				},
+				Command::Ellipse {from, to, sector, color} => {
+					w.u8(TAG_ELLIPSE); w.vec2(from); w.vec2(to); w.sector(sector); w.pixel(color);
+				},
+				Command::EllipseStroke {from, to, sector, thickness, color} => {
+					w.u8(TAG_ELLIPSE_STROKE); w.vec2(from); w.vec2(to); w.sector(sector); w.f32(thickness); w.pixel(color);
+				},
+				Command::Circle {origin, radius, color} => {
+					w.u8(TAG_CIRCLE); w.vec2(origin); w.i32(radius); w.pixel(color);
+				},
+				Command::CircleStroke {origin, radius, thickness, color} => {
+					w.u8(TAG_CIRCLE_STROKE); w.vec2(origin); w.i32(radius); w.f32(thickness); w.pixel(color);
+				},
+				Command::Triangle {points, color} => {
+					w.u8(TAG_TRIANGLE);
+					for &p in &points { w.vec2(p); }
+					w.pixel(color);
+				},
+				Command::TriangleStroke {points, thickness, color} => {
+					w.u8(TAG_TRIANGLE_STROKE);
+					for &p in &points { w.vec2(p); }
+					w.f32(thickness); w.pixel(color);
+				},
+				Command::Blit {image, from, to, filter} => {
+					w.u8(TAG_BLIT); w.u8(image); w.vec2(from); w.vec2(to); w.u8(filter as u8);
+				},
+				Command::Text {font, text, from, to, size, color} => {
+					w.u8(TAG_TEXT); w.u8(font);
+					w.bytes(&asset::u16_bytes(text.start)); w.bytes(&asset::u16_bytes(text.len));
+					w.vec2(from); w.vec2(to); w.f32(size); w.pixel(color);
+				},
+				Command::FillPath {path, rule, color} => {
+					w.u8(TAG_FILL_PATH); w.path_ref(path); w.u8(rule as u8); w.pixel(color);
+				},
+				Command::StrokePath {path, style, color} => {
+					w.u8(TAG_STROKE_PATH); w.path_ref(path); w.style(style); w.pixel(color);
+				},
+				Command::CopyTransform {image, size, pos, scale, origin, angle, skew, filter} => {
+					w.u8(TAG_COPY_TRANSFORM); w.u8(image); w.vec2(size); w.vec2(pos);
+					w.vec2f(scale); w.vec2(origin); w.f32(angle); w.vec2f(skew); w.u8(filter as u8);
+				}
+			}
+		}
+
+		let len = w.pos;
+		if w.full {
+			return None;
+		}
+
+		out[..asset::HEADER_LEN].copy_from_slice(&asset::header(AssetKind::DisplayList, P::TAG, (len - asset::HEADER_LEN) as u32));
+		Some(len)
+	}
+
+	pub fn deserialize(buf: &[u8]) -> Result<Self, AssetError> {
+		let mut r = Reader {buf: asset::payload(buf, AssetKind::DisplayList, P::TAG)?, pos: 0};
+		let mut list = Self::new();
+
+		let (len, text_len) = (r.u16()? as usize, r.u16()? as usize);
+		if len > N || text_len > TEXT {
+			return Err(AssetError::Corrupt);
+		}
+
+		list.text[..text_len].copy_from_slice(r.bytes(text_len)?);
+		list.text_len = text_len;
+
+		let paths_len = r.u16()? as usize;
+		if paths_len > PATHS {
+			return Err(AssetError::Corrupt);
+		}
+
+		for i in 0..paths_len {
+			list.paths[i] = r.path_command()?;
+		}
+
+		list.paths_len = paths_len;
+		//the box is worked out again instead of trusting the asset
+		let path_ref = |r: &mut Reader, list: &Self| -> Result<PathRef, AssetError> {
+			let (start, len, tolerance) = (r.u16()?, r.u16()?, r.f32()?);
+			let end = start as usize + len as usize;
+
+			if end > paths_len || !(tolerance > 0.0) {
+				return Err(AssetError::Corrupt);
+			}
+
+			let (from, to) = path_box(&list.paths[start as usize..end]);
+			Ok(PathRef {start, len, tolerance, from, to})
+		};
+
+		for _ in 0..len {
+			let cmd = match r.u8()? {
+				TAG_LINE => Command::Line {from: r.vec2()?, to: r.vec2()?, style: r.style()?, color: r.pixel()?},
+				TAG_ARC => Command::Arc {
+					from: r.vec2()?, to: r.vec2()?, start: r.f32()?, end: r.f32()?,
+					thickness: r.f32()?, round_caps: r.bool()?, color: r.pixel()?
+				},
+				TAG_RECT => Command::Rect {from: r.vec2()?, to: r.vec2()?, roundness: r.i32()?, color: r.pixel()?},
+				TAG_RECT_STROKE => Command::RectStroke {
+					from: r.vec2()?, to: r.vec2()?, roundness: r.i32()?, thickness: r.f32()?, color: r.pixel()?
+				},
+				TAG_ELLIPSE => Command::Ellipse {from: r.vec2()?, to: r.vec2()?, sector: r.sector()?, color: r.pixel()?},
+				TAG_ELLIPSE_STROKE => Command::EllipseStroke {
+					from: r.vec2()?, to: r.vec2()?, sector: r.sector()?, thickness: r.f32()?, color: r.pixel()?
+				},
+				TAG_CIRCLE => Command::Circle {origin: r.vec2()?, radius: r.i32()?, color: r.pixel()?},
+				TAG_CIRCLE_STROKE => Command::CircleStroke {origin: r.vec2()?, radius: r.i32()?, thickness: r.f32()?, color: r.pixel()?},
+				TAG_TRIANGLE => Command::Triangle {points: r.points()?, color: r.pixel()?},
+				TAG_TRIANGLE_STROKE => Command::TriangleStroke {points: r.points()?, thickness: r.f32()?, color: r.pixel()?},
+				TAG_BLIT => Command::Blit {image: r.u8()?, from: r.vec2()?, to: r.vec2()?, filter: r.filter()?},
+				TAG_TEXT => {
+					let font = r.u8()?;
+					let text = TextRef {start: r.u16()?, len: r.u16()?};
+
+					let (start, end) = (text.start as usize, text.start as usize + text.len as usize);
+					if end > text_len || str::from_utf8(&list.text[start..end]).is_err() {
+						return Err(AssetError::Corrupt);
+					}
+
+					Command::Text {font, text, from: r.vec2()?, to: r.vec2()?, size: r.f32()?, color: r.pixel()?}
+				},
+				TAG_FILL_PATH => Command::FillPath {path: path_ref(&mut r, &list)?, rule: r.rule()?, color: r.pixel()?},
+				TAG_STROKE_PATH => Command::StrokePath {path: path_ref(&mut r, &list)?, style: r.style()?, color: r.pixel()?},
+				TAG_COPY_TRANSFORM => Command::CopyTransform {
+					image: r.u8()?, size: r.vec2()?, pos: r.vec2()?, scale: r.vec2f()?, origin: r.vec2()?,
+					angle: r.f32()?, skew: r.vec2f()?, filter: r.filter()?
+				},
+				_ => return Err(AssetError::Corrupt)
+			};
+
+			list.push(cmd);
+		}
+
+		Ok(list)
+	}
+}
+
+//fills outline onto target like fill_path, for paths kept in the list
+fn fill<B: WriteBuffer, P: ToPixel<B::Format>, O: raster::Outline>(target: &mut B, outline: &O, rule: FillRule, color: &P) -> Result<(), RasterError> {
+	let clip = target.clip();
+
+	raster::rasterize(outline, rule, clip.from, clip.to, |x, y, len, cov| {
+		target.coverage_span(x, y, len, color, cov);
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	type List = DisplayList<u8, 8, 4, 32>;
+
+	fn draw(list: &List, images: &[&FrameBuffer<u8, 4, 4>]) -> FrameBuffer<u8, 32, 32> {
+		let mut fb = FrameBuffer::new(0);
+		let fonts: &[&StaticFontBuffer] = &[];
+
+		list.replay(&mut fb, images, fonts).unwrap();
+		fb
+	}
+
+	#[test]
+	fn paths_and_transforms_replay_like_drawing() {
+		let points = [vec2(2, 3), vec2(28, 6), vec2(6, 27), vec2(20, 20)];
+		let path = Path::<8>::new().move_to(vec2f(16.0, 2.0)).quad_to(vec2f(30.0, 16.0), vec2f(16.0, 30.0)).close();
+		let style = StrokeStyle::new(2.5);
+		let image = FrameBuffer::<u8, 4, 4>::new(200);
+
+		let mut list = List::new();
+		assert!(list.push_poly(&points, FillRule::EvenOdd, 90));
+		assert!(list.push_polyline(&points, style, 150));
+		assert!(list.push_path(&path, FillRule::NonZero, 60));
+		assert!(list.push_stroke_path(&path, style, 255));
+		assert!(list.push(Command::CopyTransform {
+			image: 0, size: vec2(4, 4), pos: vec2(10, 10), scale: vec2f(2.0, 2.0), origin: vec2(2, 2),
+			angle: 0.5, skew: vec2f(0.0, 0.0), filter: Filter::Bilinear
+		}));
+
+		let mut fb: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		fb.poly(&points, FillRule::EvenOdd, &90).unwrap();
+		fb.polyline(&points, &150, &style).unwrap();
+		fb.fill_path(&path, FillRule::NonZero, &60).unwrap();
+		fb.stroke_path(&path, &255, &style).unwrap();
+		fb.copy_transform(vec2(10, 10), vec2f(2.0, 2.0), vec2(2, 2), 0.5, vec2f(0.0, 0.0), &image, Filter::Bilinear);
+
+		assert_eq!(draw(&list, &[&image]).pixels, fb.pixels);
+
+		//and the same once stored and loaded back
+		let mut buf = [0; 1024];
+		let len = list.serialize(&mut buf).unwrap();
+		let loaded = List::deserialize(&buf[..len]).unwrap();
+
+		assert_eq!(draw(&loaded, &[&image]).pixels, fb.pixels);
+		assert!((0..list.len()).all(|i| list.get(i) == loaded.get(i)));
+	}
+
+	#[test]
+	fn full_pools_are_refused() {
+		let mut list = DisplayList::<u8, 4, 4, 4>::new();
+
+		assert!(!list.push_poly(&[vec2(0, 0); 5], FillRule::NonZero, 1));
+		assert!(list.push_poly(&[vec2(0, 0); 4], FillRule::NonZero, 1));
+		assert!(!list.push_path(&Path::<2>::new().move_to(vec2f(0.0, 0.0)), FillRule::NonZero, 1));
+		assert_eq!(list.len(), 1);
+	}
+
+	#[test]
+	fn refused_paths_leave_the_pool_alone() {
+		let mut list = DisplayList::<u8, 4, 4, 4>::new();
+		let full = Path::<2>::new().move_to(vec2f(0.0, 0.0)).line_to(vec2f(4.0, 0.0)).line_to(vec2f(0.0, 4.0));
+
+		assert!(!list.push_path(&full, FillRule::NonZero, 1));
+		assert!(!list.push_stroke_path(&full, StrokeStyle::new(1.0), 1));
+		assert_eq!((list.len(), list.paths_len), (0, 0));
+
+		//with the command list full the path isn't kept either
+		for _ in 0..4 {
+			assert!(list.push(Command::Circle {origin: vec2(4, 4), radius: 2, color: 1}));
+		}
+
+		assert!(!list.push_poly(&[vec2(0, 0), vec2(4, 0), vec2(0, 4)], FillRule::NonZero, 1));
+		assert_eq!(list.paths_len, 0);
+
+		//so the whole pool is still there for the next frame
+		list.clear();
+		assert!(list.push_poly(&[vec2(0, 0), vec2(4, 0), vec2(4, 4), vec2(0, 4)], FillRule::NonZero, 1));
+	}
+
+	#[test]
+	fn diff_marks_what_changed() {
+		let rect = |x: i32, color: u8| Command::Rect {from: vec2(x, 4), to: vec2(x + 4, 8), roundness: 0, color};
+
+		let mut prev = List::new();
+		for &cmd in &[rect(0, 1), rect(10, 1), rect(20, 1)] {
+			prev.push(cmd);
+		}
+
+		let mut next = List::new();
+		for &cmd in &[rect(0, 1), rect(12, 1), rect(20, 2)] {
+			next.push(cmd);
+		}
+
+		let mut dirty = DirtyBuffer::new(FrameBuffer::<u8, 32, 32>::new(0));
+		next.diff(&prev, &mut dirty);
+
+		//the moved rect marks where it was and where it is now, the recolored one where it is
+		assert_eq!(dirty.regions(), &[Region::new(vec2(10, 4), vec2(16, 8)), Region::new(vec2(20, 4), vec2(24, 8))]);
+
+		let mut dirty = DirtyBuffer::new(FrameBuffer::<u8, 32, 32>::new(0));
+		next.diff(&next, &mut dirty);
+		assert!(!dirty.is_dirty());
+
+		//commands only in one of the lists are marked too
+		prev.push(Command::Rect {from: vec2(0, 20), to: vec2(2, 22), roundness: 0, color: 1});
+		next.diff(&prev, &mut dirty);
+		assert_eq!(dirty.regions(), &[Region::new(vec2(10, 4), vec2(16, 8)), Region::new(vec2(20, 4), vec2(24, 8)), Region::new(vec2(0, 20), vec2(2, 22))]);
+	}
+}
@@ -1,7 +1,15 @@
 use super::*;
-use core::mem;
+use raster::{Outline, FillRule, RasterError};
+use path::{Path, Polyline, EllipseArc, InnerStroke, StrokeStyle, LineCap};
+use asset::{self, AssetPixel, AssetKind, AssetError};
+use core::char;
+use core::marker::PhantomData;
+use core::f32::consts::PI;
 
 pub trait Pixel: Clone {
+	//the smallest step the coarsest channel can take, from 0 to 1
+	const STEP: f32 = 1.0 / 255.0;
+
 	fn soft(&self) -> bool;
 
 	fn soft_blend(&self) -> f32 {
@@ -10,6 +18,12 @@ pub trait Pixel: Clone {
 
 	fn mult(self, t: f32) -> Self;
 	fn choose(self, other: Self, t: f32) -> Self;
+	//applies f to each color channel of self and dst, keeping the alpha of dst
+	fn combine<F: Fn(u8, u8) -> u8>(self, dst: Self, f: F) -> Self;
+
+	fn alpha(&self) -> f32 {
+		if self.soft() { self.soft_blend() } else { 1.0 }
+	}
 }
 
 //like Into, but for pixels to make type constraints shorter
@@ -36,12 +50,19 @@ impl Pixel for u8 {
 		(self as f32 * t) as u8
 	}
 
+	//stepping from other keeps other exactly when both are the same
 	fn choose(self, other: Self, t: f32) -> Self {
-		((self as f32 * t) + (other as f32 * (1.0-t))) as u8
+		(other as f32 + (self as f32 - other as f32) * t) as u8
+	}
+
+	fn combine<F: Fn(u8, u8) -> u8>(self, dst: Self, f: F) -> Self {
+		f(self, dst)
 	}
 }
 
-#[derive(Debug, Clone)]
+//laid out like assets store it, so rows of it can be borrowed straight from them
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct RGB(pub u8, pub u8, pub u8);
 
 impl Pixel for RGB {
@@ -56,9 +77,14 @@ impl Pixel for RGB {
 	fn choose(self, other: Self, t: f32) -> Self {
 		RGB(self.0.choose(other.0, t), self.1.choose(other.1, t), self.2.choose(other.2, t))
 	}
+
+	fn combine<F: Fn(u8, u8) -> u8>(self, dst: Self, f: F) -> Self {
+		RGB(f(self.0, dst.0), f(self.1, dst.1), f(self.2, dst.2))
+	}
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct RGBA(pub u8, pub u8, pub u8, pub u8);
 
 impl Pixel for RGBA {
@@ -77,12 +103,50 @@ impl Pixel for RGBA {
 	fn choose(self, other: Self, t: f32) -> Self {
 		RGBA(self.0.choose(other.0, t), self.1.choose(other.1, t), self.2.choose(other.2, t), self.3.choose(other.3, t))
 	}
+
+	fn combine<F: Fn(u8, u8) -> u8>(self, dst: Self, f: F) -> Self {
+		RGBA(f(self.0, dst.0), f(self.1, dst.1), f(self.2, dst.2), dst.3)
+	}
 }
 
-impl ToPixel<RGB> for RGBA {
-	fn to_pixel(self) -> RGB {
-		RGB(self.0, self.1, self.2)
+//5 bits red, 6 bits green, 5 bits blue, what most small spi displays take
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(transparent)]
+pub struct RGB565(pub u16);
+
+impl RGB565 {
+	pub fn new(r: u8, g: u8, b: u8) -> Self {
+		RGB565(((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3))
+	}
+
+	pub fn rgb(self) -> RGB {
+		let (r, g, b) = ((self.0 >> 11) as u8, ((self.0 >> 5) & 0x3f) as u8, (self.0 & 0x1f) as u8);
+		RGB((r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2))
+	}
+}
+
+impl Pixel for RGB565 {
+	const STEP: f32 = 1.0 / 31.0;
+
+	fn soft(&self) -> bool {
+		false
+	}
+
+	fn mult(self, t: f32) -> Self {
+		self.rgb().mult(t).to_pixel()
 	}
+
+	fn choose(self, other: Self, t: f32) -> Self {
+		self.rgb().choose(other.rgb(), t).to_pixel()
+	}
+
+	fn combine<F: Fn(u8, u8) -> u8>(self, dst: Self, f: F) -> Self {
+		self.rgb().combine(dst.rgb(), f).to_pixel()
+	}
+}
+
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+	((r as u32 * 77 + g as u32 * 150 + b as u32 * 29) >> 8) as u8
 }
 
 impl ToPixel<RGB> for u8 {
@@ -97,354 +161,934 @@ impl ToPixel<RGBA> for u8 {
 	}
 }
 
-pub trait Buffer {
-	type Format: Pixel;
+impl ToPixel<RGB565> for u8 {
+	fn to_pixel(self) -> RGB565 {
+		RGB565::new(self, self, self)
+	}
+}
 
-	fn width(&self) -> i32;
-	fn height(&self) -> i32;
- 
-	fn get_pixel(&self, x: i32, y: i32) -> Self::Format;
+impl ToPixel<u8> for RGB {
+	fn to_pixel(self) -> u8 {
+		luma(self.0, self.1, self.2)
+	}
 }
 
-pub trait WriteBuffer: Buffer {
-	fn set_pixel(&mut self, x: i32, y: i32, p: Self::Format);
+impl ToPixel<RGBA> for RGB {
+	fn to_pixel(self) -> RGBA {
+		RGBA(self.0, self.1, self.2, 255)
+	}
 }
 
-pub struct StaticBuffer<Format: Pixel> {
-	pub buf: &'static [u8],
-	pub format: core::marker::PhantomData<Format>
+impl ToPixel<RGB565> for RGB {
+	fn to_pixel(self) -> RGB565 {
+		RGB565::new(self.0, self.1, self.2)
+	}
 }
 
-pub fn get_bufferi<B: Buffer>(b: &B, x: i32, y: i32) -> usize {
-	((y*b.width()) + x) as usize
+impl ToPixel<u8> for RGBA {
+	fn to_pixel(self) -> u8 {
+		luma(self.0, self.1, self.2)
+	}
 }
 
-impl<F: Pixel> StaticBuffer<F> {
-	fn get_vec2(&self, x: i32, y: i32) -> usize {
-		8+(mem::size_of::<F>()*get_bufferi(self, x, y))
+impl ToPixel<RGB> for RGBA {
+	fn to_pixel(self) -> RGB {
+		RGB(self.0, self.1, self.2)
 	}
 }
 
-impl<F: Pixel> Buffer for StaticBuffer<F> {
-	type Format = F;
+impl ToPixel<RGB565> for RGBA {
+	fn to_pixel(self) -> RGB565 {
+		RGB565::new(self.0, self.1, self.2)
+	}
+}
 
-	fn width(&self) -> i32 {
-		transmute(&self.buf[0..3])
+impl ToPixel<u8> for RGB565 {
+	fn to_pixel(self) -> u8 {
+		self.rgb().to_pixel()
 	}
+}
 
-	fn height(&self) -> i32 {
-		transmute(&self.buf[4..7])
+impl ToPixel<RGB> for RGB565 {
+	fn to_pixel(self) -> RGB {
+		self.rgb()
 	}
+}
 
-	fn get_pixel(&self, x: i32, y: i32) -> F {
-		let pos = self.get_vec2(x, y);
-		transmute(&self.buf[pos..pos + mem::size_of::<F>()])
+impl ToPixel<RGBA> for RGB565 {
+	fn to_pixel(self) -> RGBA {
+		self.rgb().to_pixel()
 	}
 }
 
-#[macro_export]
-macro_rules! include_buffer {
-	($name: ident, $format: path, $path: tt) => {
-		const $name: little::drawing::StaticBuffer<$format> =
-			little::drawing::StaticBuffer {
-				buf: include_bytes!($path),
-				format: core::marker::PhantomData
-			};
-	};
+const BAYER: [[u8; 4]; 4] = [
+	[0, 8, 2, 10],
+	[12, 4, 14, 6],
+	[3, 11, 1, 9],
+	[15, 7, 13, 5]
+];
+
+//ordered dithering threshold for a pixel, from 0 to 1
+pub fn bayer(x: i32, y: i32) -> f32 {
+	(BAYER[(y & 3) as usize][(x & 3) as usize] as f32 + 0.5) / 16.0
 }
 
-#[macro_export]
-macro_rules! create_buffer {
-	($name: ident, $width: tt, $height: tt, $format: path) => {
-		struct $name {
-			data: [$format; $width * $height]
-		}
+pub trait Buffer {
+	type Format: Pixel;
 
-		impl $name {
-			pub fn new() -> Self {
-				$name {data: [0; $width * $height]}
-			}
+	fn width(&self) -> i32;
+	fn height(&self) -> i32;
+ 
+	fn get_pixel(&self, x: i32, y: i32) -> Self::Format;
+
+	//row y as a slice, for buffers that store their pixels that way so copies can take whole rows
+	fn get_row(&self, _y: i32) -> Option<&[Self::Format]> {
+		None
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+	SrcOver,
+	//only where the destination already has something
+	SrcIn,
+	//erases the destination where drawn
+	DstOut,
+	Multiply,
+	Screen,
+	Additive,
+	//writes the source as is, alpha included
+	Replace
+}
+
+impl BlendMode {
+	//alpha is the alpha of src before it was converted to the format of dst
+	pub fn apply<P: Pixel>(self, src: P, alpha: f32, dst: P) -> P {
+		match self {
+			BlendMode::SrcOver => src.choose(dst, alpha),
+			//formats without alpha have nowhere to store the result, so they fall back to over
+			BlendMode::SrcIn => if dst.soft() {
+				src.mult(dst.alpha()).choose(dst, alpha)
+			} else {
+				src.choose(dst, alpha)
+			},
+			//and can't be erased, so they stay as they are
+			BlendMode::DstOut => if dst.soft() {
+				dst.mult(1.0 - alpha)
+			} else {
+				dst
+			},
+			BlendMode::Multiply => src.combine(dst.clone(), |s, d| (s as u16 * d as u16 / 255) as u8).choose(dst, alpha),
+			BlendMode::Screen => src.combine(dst.clone(), |s, d| 255 - ((255 - s) as u16 * (255 - d) as u16 / 255) as u8).choose(dst, alpha),
+			BlendMode::Additive => src.combine(dst, |s, d| (d as f32 + s as f32 * alpha).min(255.0) as u8),
+			BlendMode::Replace => src
 		}
+	}
+}
 
-		impl Buffer for $name {
-			type Format = $format;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+	Nearest,
+	//blends the four closest pixels, and fades the edges of transformed buffers
+	Bilinear
+}
 
-			fn width(&self) -> i32 {
-				$width
-			}
+pub trait WriteBuffer: Buffer {
+	fn set_pixel(&mut self, x: i32, y: i32, p: Self::Format);
 
-			fn height(&self) -> i32 {
-				$height
-			}
+	//how Drawing composites onto this buffer
+	fn blend_mode(&self) -> BlendMode {
+		BlendMode::SrcOver
+	}
 
-			fn get_pixel(&self, x: i32, y: i32) -> Self::Format {
-				self.data[get_bufferi(self, x, y)].clone()
-			}
-		}
+	//the only pixels drawing can change, shapes outside it are skipped
+	fn clip(&self) -> Region {
+		Region::new(vec2(0, 0), vec2(self.width(), self.height()))
+	}
 
-		impl WriteBuffer for Buffer {
-			fn set_pixel(x: i32, y: i32, p: Self::Format) {
-				self.data[get_bufferi(self, x, y)] = p;
-			}
+	//spans are len pixels from x along row y, and like set_pixel have to be inside the buffer
+	//override them where rows are contiguous, drawing fills shapes a span at a time
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: Self::Format) {
+		for x in x..x+len {
+			self.set_pixel(x, y, p.clone());
 		}
-	};
-}
+	}
 
-pub const DEFAULT_CHARS: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz:;\"\'./?!@#$%^&*(),-=+1234567890 ";
-pub const DEFAULT_LINE_HEIGHT: f32 = 32.0;
+	//composites p over the span with blend_mode, alpha is the alpha of p before it was converted
+	fn blend_span(&mut self, x: i32, y: i32, len: i32, p: Self::Format, alpha: f32) {
+		let mode = self.blend_mode();
 
-#[derive(Clone, Debug)]
-pub struct FontCharHeader {
-	pub width: i32, pub height: i32,
-	pub left: i32, pub top: i32,
+		for x in x..x+len {
+			let dst = self.get_pixel(x, y);
+			self.set_pixel(x, y, mode.apply(p.clone(), alpha, dst));
+		}
+	}
 
-	pub x_advance: f32
+	//writes pixels as they are, converted to the format of the buffer
+	fn copy_row<P: ToPixel<Self::Format>>(&mut self, x: i32, y: i32, pixels: &[P]) {
+		for (i, p) in pixels.iter().enumerate() {
+			self.set_pixel(x + i as i32, y, p.clone().to_pixel());
+		}
+	}
 }
 
-pub trait CharBuffer: Buffer<Format=u8> {
-	fn get_header(&self) -> &FontCharHeader;
+//pixels stored row after row like in assets, borrowed from anywhere
+pub struct SliceBuffer<'a, Format: AssetPixel> {
+	width: i32,
+	height: i32,
+	pixels: &'a [u8],
+	format: PhantomData<Format>
 }
 
-pub trait FontBuffer {
-	type Glyph: CharBuffer;
+//an image packed by little_util pack-image, see asset for the layout
+pub type StaticBuffer<Format> = SliceBuffer<'static, Format>;
 
-	fn get_char(&self, c: char) -> Option<Self::Glyph>;
-	fn get_kerning(&self, c1: char, c2: char) -> Option<f32>;
+pub fn get_bufferi<B: Buffer>(b: &B, x: i32, y: i32) -> usize {
+	((y*b.width()) + x) as usize
 }
 
-pub type FontCharKernPair = (char, char, f32);
+impl<'a, F: AssetPixel> SliceBuffer<'a, F> {
+	pub fn new(pixels: &'a [u8], width: i32, height: i32) -> Result<Self, AssetError> {
+		if width < 0 || height < 0 {
+			return Err(AssetError::Corrupt);
+		}
 
-pub struct StaticFontBuffer {
-	pub buf: &'static [u8]
-}
+		let pixels = asset::slice(pixels, 0, asset::area(width, height, F::SIZE)?)?;
+		Ok(SliceBuffer {width, height, pixels, format: PhantomData})
+	}
 
-pub struct StaticGlyphBuffer {
-	pub header: FontCharHeader,
-	pub buf: &'static [u8],
-	pub pos: usize
+	pub fn load(buf: &'a [u8]) -> Result<Self, AssetError> {
+		let payload = asset::payload(buf, AssetKind::Image, F::TAG)?;
+		let (width, height) = asset::size(payload)?;
+
+		SliceBuffer::new(&payload[8..], width, height)
+	}
+
+	pub fn pixels(&self) -> &'a [u8] {
+		self.pixels
+	}
 }
 
-impl Buffer for StaticGlyphBuffer {
-	type Format = u8;
+impl<'a, F: AssetPixel> Buffer for SliceBuffer<'a, F> {
+	type Format = F;
 
 	fn width(&self) -> i32 {
-		self.header.width
+		self.width
 	}
 
 	fn height(&self) -> i32 {
-		self.header.height
+		self.height
 	}
 
-	fn get_pixel(&self, x: i32, y: i32) -> u8 {
-		self.buf[self.pos+get_bufferi(self, x, y)]
+	fn get_pixel(&self, x: i32, y: i32) -> F {
+		F::read(&self.pixels[get_bufferi(self, x, y)*F::SIZE..])
 	}
-}
 
-impl CharBuffer for StaticGlyphBuffer {
-	fn get_header(&self) -> &FontCharHeader {
-		&self.header
+	fn get_row(&self, y: i32) -> Option<&[F]> {
+		F::cast(row_bytes(self.pixels, self.width, y, F::SIZE))
 	}
 }
 
-impl StaticFontBuffer {
-	fn pair_len(&self) -> usize {
-		transmute::<u32>(&self.buf[0..mem::size_of::<u32>()]) as usize
-	}
+//the bytes of row y in pixels stored row after row
+fn row_bytes(pixels: &[u8], width: i32, y: i32, size: usize) -> &[u8] {
+	let stride = width as usize*size;
+	&pixels[y as usize*stride..(y as usize + 1)*stride]
 }
 
-impl FontBuffer for StaticFontBuffer {
-	type Glyph = StaticGlyphBuffer;
-
-	fn get_char(&self, c: char) -> Option<StaticGlyphBuffer> {
-		let mut pos = mem::size_of::<u32>() + (self.pair_len() * mem::size_of::<FontCharKernPair>());
-		
-		while pos < self.buf.len() {
-			let c2: char = transmute(&self.buf[pos..pos+mem::size_of::<char>()]);
-			pos += mem::size_of::<char>();
+//like SliceBuffer, but can be drawn on
+pub struct SliceBufferMut<'a, Format: AssetPixel> {
+	width: i32,
+	height: i32,
+	pixels: &'a mut [u8],
+	format: PhantomData<Format>
+}
 
-			if c == c2 {
-				pos += mem::size_of::<u32>();
-				let header: FontCharHeader = transmute(&self.buf[pos..pos+mem::size_of::<FontCharHeader>()]);
+impl<'a, F: AssetPixel> SliceBufferMut<'a, F> {
+	pub fn new(pixels: &'a mut [u8], width: i32, height: i32) -> Result<Self, AssetError> {
+		if width < 0 || height < 0 {
+			return Err(AssetError::Corrupt);
+		}
 
-				return Some(StaticGlyphBuffer {
-					header, buf: self.buf,
-					pos: pos + mem::size_of::<FontCharHeader>()
-				});
-			} else {
-				let buf_size = transmute::<u32>(&self.buf[pos..pos+mem::size_of::<u32>()]) as usize;
-				pos += buf_size + mem::size_of::<u32>() + mem::size_of::<FontCharHeader>();
-			}
+		let len = asset::area(width, height, F::SIZE)?;
+		if pixels.len() < len {
+			return Err(AssetError::TooShort);
 		}
 
-		None
+		Ok(SliceBufferMut {width, height, pixels: &mut pixels[..len], format: PhantomData})
 	}
 
-	fn get_kerning(&self, c1: char, c2: char) -> Option<f32> {
-		for i in 0..self.pair_len() {
-			let pos = i*mem::size_of::<FontCharKernPair>();
-			let pair: FontCharKernPair = transmute(&self.buf[pos..pos+mem::size_of::<FontCharKernPair>()]);
-
-			if pair.0 == c1 && pair.1 == c2 {
-				return Some(pair.2);
-			}
-		}
+	pub fn pixels(&self) -> &[u8] {
+		self.pixels
+	}
 
-		None
+	pub fn pixels_mut(&mut self) -> &mut [u8] {
+		self.pixels
 	}
 }
 
-pub struct DrawText<'a, F: FontBuffer> {
-	pub font_size: f32,
-	pub line_height: f32,
+impl<'a, F: AssetPixel> Buffer for SliceBufferMut<'a, F> {
+	type Format = F;
 
-	pub font: &'a F,
-	pub txt: &'a str
+	fn width(&self) -> i32 {
+		self.width
+	}
+
+	fn height(&self) -> i32 {
+		self.height
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> F {
+		F::read(&self.pixels[get_bufferi(self, x, y)*F::SIZE..])
+	}
+
+	fn get_row(&self, y: i32) -> Option<&[F]> {
+		F::cast(row_bytes(self.pixels, self.width, y, F::SIZE))
+	}
 }
 
-impl<'a, F: FontBuffer> DrawText<'a, F> {
-	pub fn new(font: &'a F, txt: &'a str) -> Self {
-		DrawText {
-			font_size: 1.0, line_height: 1.0,
-			font, txt
-		}
+impl<'a, F: AssetPixel> WriteBuffer for SliceBufferMut<'a, F> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: F) {
+		let i = get_bufferi(self, x, y)*F::SIZE;
+		p.write(&mut self.pixels[i..]);
 	}
 
-	pub fn font_size(self, font_size: f32) -> Self {
-		DrawText {font_size, ..self}
+	//writes the first pixel and copies its bytes along the span
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: F) {
+		if len <= 0 {
+			return;
+		}
+
+		let i = get_bufferi(self, x, y)*F::SIZE;
+		let span = &mut self.pixels[i..i + len as usize*F::SIZE];
+		p.write(span);
+
+		for x in 1..len as usize {
+			span.copy_within(0..F::SIZE, x*F::SIZE);
+		}
 	}
-	
-	pub fn line_height(self, line_height: f32) -> Self {
-		DrawText {line_height, ..self}
+
+	fn copy_row<P: ToPixel<F>>(&mut self, x: i32, y: i32, pixels: &[P]) {
+		let i = get_bufferi(self, x, y)*F::SIZE;
+
+		for (p, b) in pixels.iter().zip(self.pixels[i..i + pixels.len()*F::SIZE].chunks_mut(F::SIZE)) {
+			p.clone().to_pixel().write(b);
+		}
 	}
 }
 
 #[macro_export]
-macro_rules! include_font {
-	($name: ident, $path: tt) => {
-		const $name: little::drawing::StaticFontBuffer =
-			little::drawing::StaticFontBuffer {
-				buf: include_bytes!($path)
-			};
+macro_rules! include_buffer {
+	($format: path, $path: tt) => {
+		little::drawing::StaticBuffer::<$format>::load(include_bytes!($path))
 	};
 }
 
-fn blend_multiply<P: Pixel, SP: Pixel, Source: FnOnce() -> SP>(color: P, p: Source) -> P {
-	let t = p().soft_blend();
-	color.mult(t)
-}
-
-pub struct DrawRegion<'a, T> {
-	pub draw: &'a mut T,
-	pub region: &'a Region
+//an image stored as indices into a palette of colors, packed from the most significant bit
+//rows start on a new byte
+pub struct SliceIndexedBuffer<'a, Format: AssetPixel> {
+	width: i32,
+	height: i32,
+	bits: u8,
+	palette: &'a [u8],
+	indices: &'a [u8],
+	format: PhantomData<Format>
 }
 
-pub struct DrawColor<'a, P: Pixel, T> {
-	pub draw: &'a mut T,
-	pub fill: &'a P
-}
+pub type StaticIndexedBuffer<Format> = SliceIndexedBuffer<'static, Format>;
 
-pub struct DrawMask<'a, B: Buffer, T> {
-	pub draw: &'a mut T,
-	pub mask: &'a B
-}
+impl<'a, F: AssetPixel> SliceIndexedBuffer<'a, F> {
+	pub fn load(buf: &'a [u8]) -> Result<Self, AssetError> {
+		let payload = asset::payload(buf, AssetKind::Indexed, F::TAG)?;
+		let (width, height) = asset::size(payload)?;
 
-impl<'a, P: Pixel, T: Buffer<Format=P>> Buffer for DrawRegion<'a, T> {
-	type Format = P;
+		let fields = asset::slice(payload, 8, 4)?;
+		let (bits, palette_len) = (fields[0], asset::read_u16(&fields[2..4]) as usize);
 
-	fn width(&self) -> i32 {
-		self.region.to.x - self.region.from.x
-	}
+		if ![1, 2, 4, 8].contains(&bits) || palette_len == 0 || palette_len > 1 << bits {
+			return Err(AssetError::Corrupt);
+		}
 
-	fn height(&self) -> i32 {
-		self.region.to.y - self.region.from.y
-	}
+		let palette = asset::slice(payload, 12, palette_len*F::SIZE)?;
+		let stride = (asset::area(width, bits as i32, 1)? + 7) / 8;
+		let indices = asset::slice(payload, 12 + palette.len(), stride.checked_mul(height as usize).ok_or(AssetError::Corrupt)?)?;
 
-	fn get_pixel(&self, x: i32, y: i32) -> P {
-		self.draw.get_pixel(self.region.from.x + x, self.region.from.y + y)
-	}
-}
+		let img = SliceIndexedBuffer {width, height, bits, palette, indices, format: PhantomData};
 
-impl<'a, P: Pixel, T: Buffer<Format=P> + WriteBuffer> WriteBuffer for DrawRegion<'a, T> {
-	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
-		let x = vec2(self.region.from.x + x, self.region.from.y + y);
-		//scissor
-		if self.inside(x) {
-			self.draw.set_pixel(x.x, x.y, p);
+		//indices past the palette would read garbage later
+		if palette_len < 1 << bits {
+			for y in 0..height {
+				for x in 0..width {
+					if img.get_index(x, y) >= palette_len {
+						return Err(AssetError::Corrupt);
+					}
+				}
+			}
 		}
+
+		Ok(img)
 	}
-}
 
-impl<'a, P: Pixel, SP: Pixel, T: Buffer<Format=SP>> Buffer for DrawColor<'a, P, T> {
-	type Format = P;
+	//1, 2, 4 or 8
+	pub fn bits(&self) -> u8 {
+		self.bits
+	}
 
-	fn width(&self) -> i32 {
-		self.draw.width()
+	pub fn palette_len(&self) -> usize {
+		self.palette.len() / F::SIZE
 	}
 
-	fn height(&self) -> i32 {
-		self.draw.height()
+	pub fn palette(&self, i: usize) -> F {
+		F::read(&self.palette[i*F::SIZE..])
 	}
 
-	fn get_pixel(&self, x: i32, y: i32) -> P {
-		blend_multiply(self.fill.clone(), || self.draw.get_pixel(x, y))
+	fn stride(&self) -> usize {
+		(self.width as usize * self.bits as usize + 7) / 8
 	}
-}
 
-impl<'a, P: ToPixel<SP>, SP: Pixel, T: Buffer<Format=SP> + WriteBuffer> WriteBuffer for DrawColor<'a, P, T> {
-	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
-		self.draw.set_pixel(x, y, blend_multiply(self.fill.clone(), move || p).to_pixel());
+	pub fn get_index(&self, x: i32, y: i32) -> usize {
+		let bits = self.bits as usize;
+		let bit = x as usize * bits;
+		let byte = self.indices[y as usize*self.stride() + bit/8];
+
+		(byte >> (8 - bits - bit%8)) as usize & ((1 << bits) - 1)
 	}
 }
 
-impl<'a, B: Buffer, P: Pixel, T: Buffer<Format=P>> Buffer for DrawMask<'a, B, T> {
-	type Format = P;
+impl<'a, F: AssetPixel> Buffer for SliceIndexedBuffer<'a, F> {
+	type Format = F;
 
 	fn width(&self) -> i32 {
-		self.draw.width()
+		self.width
 	}
 
 	fn height(&self) -> i32 {
-		self.draw.height()
+		self.height
 	}
 
-	fn get_pixel(&self, x: i32, y: i32) -> P {
-		blend_multiply(self.draw.get_pixel(x, y), || self.mask.get_pixel(x, y))
+	fn get_pixel(&self, x: i32, y: i32) -> F {
+		self.palette(self.get_index(x, y))
 	}
 }
 
-impl<'a, B: Buffer, P: Pixel, T: Buffer<Format=P> + WriteBuffer> WriteBuffer for DrawMask<'a, B, T> {
-	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
-		self.draw.set_pixel(x, y, blend_multiply(p, || self.mask.get_pixel(x, y)));
-	}
+#[macro_export]
+macro_rules! include_indexed_buffer {
+	($format: path, $path: tt) => {
+		little::drawing::StaticIndexedBuffer::<$format>::load(include_bytes!($path))
+	};
 }
 
-pub trait DrawingConvert: Sized {
-	fn with_region<'a>(&'a mut self, region: &'a Region) -> DrawRegion<'a, Self>;
-	fn with_color<'a, C: Pixel>(&'a mut self, fill: &'a C) -> DrawColor<'a, C, Self>;
-	fn with_mask<'a, B: Buffer>(&'a mut self, mask: &'a B) -> DrawMask<'a, B, Self>;
+//pixels in ram, can be put in a static to draw off-screen
+pub struct FrameBuffer<P: Pixel + Copy, const W: usize, const H: usize> {
+	pub pixels: [[P; W]; H]
 }
 
-pub trait Drawing<P: Pixel, TP: ToPixel<P>> {
-	fn blend(&mut self, x: i32, y: i32, color: TP);
+impl<P: Pixel + Copy, const W: usize, const H: usize> FrameBuffer<P, W, H> {
+	pub const fn new(fill: P) -> Self {
+		FrameBuffer {pixels: [[fill; W]; H]}
+	}
 
-	fn antialiased_blend_x(&mut self, x: f32, y: i32, color: TP);
-	fn antialiased_blend_y(&mut self, x: i32, y: f32, color: TP);
+	pub fn row(&self, y: i32) -> &[P] {
+		&self.pixels[y as usize]
+	}
 
-	fn antialiased_blend_x_dir(&mut self, x: f32, y: i32, right: bool, color: TP);
-	fn antialiased_blend_y_dir(&mut self, x: i32, y: f32, right: bool, color: TP);
+	pub fn row_mut(&mut self, y: i32) -> &mut [P] {
+		&mut self.pixels[y as usize]
+	}
 
-	fn antialiased_blend(&mut self, x: f32, y: f32, color: TP);
+	pub fn clear(&mut self, p: P) {
+		if H == 0 {
+			return;
+		}
 
-	fn line(&mut self, from: Vector2, to: Vector2, color: &TP, thickness: i32);
+		//fill one row and copy it over the rest
+		let (first, rest) = self.pixels.split_at_mut(1);
+		for x in first[0].iter_mut() {
+			*x = p;
+		}
+
+		for row in rest {
+			row.copy_from_slice(&first[0]);
+		}
+	}
+
+	//fills from..to without blending, clipped to the buffer
+	pub fn fill(&mut self, from: Vector2, to: Vector2, p: P) {
+		let (x0, x1) = (from.x.max(0) as usize, to.x.max(0).min(W as i32) as usize);
+		let (y0, y1) = (from.y.max(0) as usize, to.y.max(0).min(H as i32) as usize);
+
+		if x0 >= x1 {
+			return;
+		}
+
+		for row in &mut self.pixels[y0.min(y1)..y1] {
+			for x in row[x0..x1].iter_mut() {
+				*x = p;
+			}
+		}
+	}
+}
+
+impl<P: Pixel + Copy, const W: usize, const H: usize> Buffer for FrameBuffer<P, W, H> {
+	type Format = P;
+
+	fn width(&self) -> i32 {
+		W as i32
+	}
+
+	fn height(&self) -> i32 {
+		H as i32
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> P {
+		self.pixels[y as usize][x as usize]
+	}
+
+	fn get_row(&self, y: i32) -> Option<&[P]> {
+		Some(&self.pixels[y as usize])
+	}
+}
+
+impl<P: Pixel + Copy, const W: usize, const H: usize> WriteBuffer for FrameBuffer<P, W, H> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
+		self.pixels[y as usize][x as usize] = p;
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: P) {
+		for px in &mut self.pixels[y as usize][x as usize..(x + len) as usize] {
+			*px = p;
+		}
+	}
+
+	fn blend_span(&mut self, x: i32, y: i32, len: i32, p: P, alpha: f32) {
+		let mode = self.blend_mode();
+
+		for px in &mut self.pixels[y as usize][x as usize..(x + len) as usize] {
+			*px = mode.apply(p, alpha, *px);
+		}
+	}
+
+	fn copy_row<SP: ToPixel<P>>(&mut self, x: i32, y: i32, pixels: &[SP]) {
+		for (px, p) in self.pixels[y as usize][x as usize..x as usize + pixels.len()].iter_mut().zip(pixels) {
+			*px = p.clone().to_pixel();
+		}
+	}
+}
+
+pub const DEFAULT_CHARS: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz:;\"\'./?!@#$%^&*(),-=+1234567890 ";
+pub const DEFAULT_LINE_HEIGHT: f32 = 32.0;
+
+#[derive(Clone, Debug)]
+pub struct FontCharHeader {
+	pub width: i32, pub height: i32,
+	pub left: i32, pub top: i32,
+
+	pub x_advance: f32
+}
+
+pub trait CharBuffer: Buffer<Format=u8> {
+	fn get_header(&self) -> &FontCharHeader;
+}
+
+pub trait FontBuffer {
+	type Glyph: CharBuffer;
+
+	fn get_char(&self, c: char) -> Option<Self::Glyph>;
+	fn get_kerning(&self, c1: char, c2: char) -> Option<f32>;
+}
+
+pub type FontCharKernPair = (char, char, f32);
+
+//a font packed by little_util pack-font, see asset for the layout
+pub struct SliceFontBuffer<'a> {
+	pairs: &'a [u8],
+	glyphs: &'a [u8]
+}
+
+pub type StaticFontBuffer = SliceFontBuffer<'static>;
+
+pub struct SliceGlyphBuffer<'a> {
+	pub header: FontCharHeader,
+	pub pixels: &'a [u8]
+}
+
+pub type StaticGlyphBuffer = SliceGlyphBuffer<'static>;
+
+impl<'a> Buffer for SliceGlyphBuffer<'a> {
+	type Format = u8;
+
+	fn width(&self) -> i32 {
+		self.header.width
+	}
+
+	fn height(&self) -> i32 {
+		self.header.height
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> u8 {
+		self.pixels[get_bufferi(self, x, y)]
+	}
+}
+
+impl<'a> CharBuffer for SliceGlyphBuffer<'a> {
+	fn get_header(&self) -> &FontCharHeader {
+		&self.header
+	}
+}
+
+const KERNING_PAIR_LEN: usize = 12;
+const GLYPH_HEADER_LEN: usize = 28;
+
+impl<'a> SliceFontBuffer<'a> {
+	pub fn load(buf: &'a [u8]) -> Result<Self, AssetError> {
+		let payload = asset::payload(buf, AssetKind::Font, u8::TAG)?;
+		let pair_len = asset::read_u32(asset::slice(payload, 0, 4)?) as usize;
+
+		let pairs = asset::slice(payload, 4, pair_len.checked_mul(KERNING_PAIR_LEN).ok_or(AssetError::Corrupt)?)?;
+		let font = SliceFontBuffer {pairs, glyphs: &payload[4 + pairs.len()..]};
+
+		let mut pos = 0;
+		while pos < font.glyphs.len() {
+			pos = font.glyph(pos)?.2;
+		}
+
+		Ok(font)
+	}
+
+	//the glyph at pos and where the next one starts
+	fn glyph(&self, pos: usize) -> Result<(char, SliceGlyphBuffer<'a>, usize), AssetError> {
+		let b = asset::slice(self.glyphs, pos, GLYPH_HEADER_LEN)?;
+
+		let c = char::from_u32(asset::read_u32(&b[0..4])).ok_or(AssetError::Corrupt)?;
+		let len = asset::read_u32(&b[4..8]) as usize;
+
+		let header = FontCharHeader {
+			width: asset::read_i32(&b[8..12]), height: asset::read_i32(&b[12..16]),
+			left: asset::read_i32(&b[16..20]), top: asset::read_i32(&b[20..24]),
+
+			x_advance: asset::read_f32(&b[24..28])
+		};
+
+		if header.width < 0 || header.height < 0 || asset::area(header.width, header.height, 1)? != len {
+			return Err(AssetError::Corrupt);
+		}
+
+		let pixels = asset::slice(self.glyphs, pos + GLYPH_HEADER_LEN, len)?;
+		Ok((c, SliceGlyphBuffer {header, pixels}, pos + GLYPH_HEADER_LEN + len))
+	}
+}
+
+impl<'a> FontBuffer for SliceFontBuffer<'a> {
+	type Glyph = SliceGlyphBuffer<'a>;
+
+	fn get_char(&self, c: char) -> Option<SliceGlyphBuffer<'a>> {
+		let mut pos = 0;
+
+		while pos < self.glyphs.len() {
+			let (c2, glyph, next) = self.glyph(pos).ok()?;
+
+			if c == c2 {
+				return Some(glyph);
+			}
+
+			pos = next;
+		}
+
+		None
+	}
+
+	fn get_kerning(&self, c1: char, c2: char) -> Option<f32> {
+		for pair in self.pairs.chunks(KERNING_PAIR_LEN) {
+			if asset::read_u32(&pair[0..4]) == c1 as u32 && asset::read_u32(&pair[4..8]) == c2 as u32 {
+				return Some(asset::read_f32(&pair[8..12]));
+			}
+		}
+
+		None
+	}
+}
+
+pub struct DrawText<'a, F: FontBuffer> {
+	pub font_size: f32,
+	pub line_height: f32,
+
+	pub font: &'a F,
+	pub txt: &'a str
+}
+
+impl<'a, F: FontBuffer> DrawText<'a, F> {
+	pub fn new(font: &'a F, txt: &'a str) -> Self {
+		DrawText {
+			font_size: 1.0, line_height: 1.0,
+			font, txt
+		}
+	}
+
+	pub fn font_size(self, font_size: f32) -> Self {
+		DrawText {font_size, ..self}
+	}
+	
+	pub fn line_height(self, line_height: f32) -> Self {
+		DrawText {line_height, ..self}
+	}
+}
+
+#[macro_export]
+macro_rules! include_font {
+	($path: tt) => {
+		little::drawing::StaticFontBuffer::load(include_bytes!($path))
+	};
+}
+
+pub struct DrawRegion<'a, T> {
+	pub draw: &'a mut T,
+	pub region: &'a Region
+}
+
+//reads as fill wherever draw has alpha, and paints fill as much as what is drawn on it covers
+//only formats with alpha can carry the coverage, others read and paint the whole fill
+pub struct DrawColor<'a, P: Pixel, T> {
+	pub draw: &'a mut T,
+	pub fill: &'a P
+}
+
+//draws onto draw only as much as mask covers, like the edges of a shape
+pub struct DrawMask<'a, B: Buffer, T> {
+	pub draw: &'a mut T,
+	pub mask: &'a B
+}
+
+pub struct DrawBlend<'a, T> {
+	pub draw: &'a mut T,
+	pub mode: BlendMode
+}
+
+impl<'a, P: Pixel, T: Buffer<Format=P>> Buffer for DrawRegion<'a, T> {
+	type Format = P;
+
+	fn width(&self) -> i32 {
+		self.region.to.x - self.region.from.x
+	}
+
+	fn height(&self) -> i32 {
+		self.region.to.y - self.region.from.y
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> P {
+		self.draw.get_pixel(self.region.from.x + x, self.region.from.y + y)
+	}
+
+	fn get_row(&self, y: i32) -> Option<&[P]> {
+		let r = self.region;
+		self.draw.get_row(r.from.y + y).map(|row| &row[r.from.x as usize..r.to.x as usize])
+	}
+}
+
+impl<'a, P: Pixel, T: Buffer<Format=P> + WriteBuffer> WriteBuffer for DrawRegion<'a, T> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
+		//scissor
+		if self.inside(vec2(x, y)) {
+			self.draw.set_pixel(self.region.from.x + x, self.region.from.y + y, p);
+		}
+	}
+
+	fn blend_mode(&self) -> BlendMode {
+		self.draw.blend_mode()
+	}
+
+	fn clip(&self) -> Region {
+		let clip = self.draw.clip();
+		let from = self.region.from;
+
+		Region::new(clip.from - from, clip.to - from).intersect(Region::new(vec2(0, 0), vec2(self.width(), self.height())))
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: P) {
+		if let Some((x, y, len)) = self.to_parent(x, y, len) {
+			self.draw.fill_span(x, y, len, p);
+		}
+	}
+
+	fn blend_span(&mut self, x: i32, y: i32, len: i32, p: P, alpha: f32) {
+		if let Some((x, y, len)) = self.to_parent(x, y, len) {
+			self.draw.blend_span(x, y, len, p, alpha);
+		}
+	}
+
+	fn copy_row<SP: ToPixel<P>>(&mut self, x: i32, y: i32, pixels: &[SP]) {
+		let skip = (-x).max(0).min(pixels.len() as i32);
+
+		if let Some((x, y, len)) = self.to_parent(x, y, pixels.len() as i32) {
+			self.draw.copy_row(x, y, &pixels[skip as usize..(skip + len) as usize]);
+		}
+	}
+}
+
+impl<'a, T> DrawRegion<'a, T> {
+	//moves a span into the region's parent, cut to the region like set_pixel
+	fn to_parent(&self, x: i32, y: i32, len: i32) -> Option<(i32, i32, i32)> {
+		let r = self.region;
+		let (from, to) = ((r.from.x + x).max(r.from.x), (r.from.x + x + len).min(r.to.x));
+
+		if y < 0 || r.from.y + y >= r.to.y || from >= to {
+			None
+		} else {
+			Some((from, r.from.y + y, to - from))
+		}
+	}
+}
+
+impl<'a, P: Pixel, SP: Pixel, T: Buffer<Format=SP>> Buffer for DrawColor<'a, P, T> {
+	type Format = P;
+
+	fn width(&self) -> i32 {
+		self.draw.width()
+	}
+
+	fn height(&self) -> i32 {
+		self.draw.height()
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> P {
+		self.fill.clone().mult(self.draw.get_pixel(x, y).alpha())
+	}
+}
+
+impl<'a, P: ToPixel<SP>, SP: Pixel, T: Buffer<Format=SP> + WriteBuffer> WriteBuffer for DrawColor<'a, P, T> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
+		let mode = self.draw.blend_mode();
+		put_covered(self.draw, x, y, self.fill.clone(), mode, p.alpha());
+	}
+
+	//what is drawn here only says how much to cover, draw's own mode blends the fill
+	fn blend_mode(&self) -> BlendMode {
+		BlendMode::Replace
+	}
+
+	fn clip(&self) -> Region {
+		self.draw.clip()
+	}
+}
+
+impl<'a, B: Buffer, P: Pixel, T: Buffer<Format=P>> Buffer for DrawMask<'a, B, T> {
+	type Format = P;
+
+	fn width(&self) -> i32 {
+		self.draw.width()
+	}
+
+	fn height(&self) -> i32 {
+		self.draw.height()
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> P {
+		//drawing blends with what is really there, the mask only fades the result
+		self.draw.get_pixel(x, y)
+	}
+}
+
+impl<'a, B: Buffer, P: Pixel, T: Buffer<Format=P> + WriteBuffer> WriteBuffer for DrawMask<'a, B, T> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
+		let dst = self.draw.get_pixel(x, y);
+		self.draw.set_pixel(x, y, p.choose(dst, self.mask.get_pixel(x, y).alpha()));
+	}
+
+	fn blend_mode(&self) -> BlendMode {
+		self.draw.blend_mode()
+	}
+
+	fn clip(&self) -> Region {
+		self.draw.clip()
+	}
+}
+
+impl<'a, P: Pixel, T: Buffer<Format=P>> Buffer for DrawBlend<'a, T> {
+	type Format = P;
+
+	fn width(&self) -> i32 {
+		self.draw.width()
+	}
+
+	fn height(&self) -> i32 {
+		self.draw.height()
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> P {
+		self.draw.get_pixel(x, y)
+	}
+
+	fn get_row(&self, y: i32) -> Option<&[P]> {
+		self.draw.get_row(y)
+	}
+}
+
+impl<'a, P: Pixel, T: Buffer<Format=P> + WriteBuffer> WriteBuffer for DrawBlend<'a, T> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: P) {
+		self.draw.set_pixel(x, y, p);
+	}
+
+	fn blend_mode(&self) -> BlendMode {
+		self.mode
+	}
+
+	fn clip(&self) -> Region {
+		self.draw.clip()
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: P) {
+		self.draw.fill_span(x, y, len, p);
+	}
+
+	fn copy_row<SP: ToPixel<P>>(&mut self, x: i32, y: i32, pixels: &[SP]) {
+		self.draw.copy_row(x, y, pixels);
+	}
+}
+
+pub trait DrawingConvert: Sized {
+	fn with_region<'a>(&'a mut self, region: &'a Region) -> DrawRegion<'a, Self>;
+	fn with_color<'a, C: Pixel>(&'a mut self, fill: &'a C) -> DrawColor<'a, C, Self>;
+	fn with_mask<'a, B: Buffer>(&'a mut self, mask: &'a B) -> DrawMask<'a, B, Self>;
+	fn with_blend<'a>(&'a mut self, mode: BlendMode) -> DrawBlend<'a, Self>;
+}
+
+pub trait Drawing<P: Pixel, TP: ToPixel<P>> {
+	fn blend(&mut self, x: i32, y: i32, color: TP);
+	fn blend_with(&mut self, x: i32, y: i32, color: TP, mode: BlendMode);
+
+
+	fn coverage_blend(&mut self, x: i32, y: i32, color: &TP, coverage: f32);
+	fn coverage_span(&mut self, x: i32, y: i32, len: i32, color: &TP, coverage: f32);
+
+	//every shape is filled by raster::rasterize, points are pixel corners so pixel (x, y) covers x..x+1 and y..y+1
+	//shapes made of any number of points return an error when too many of their edges pass through one pixel
+	//the fixed shapes can't have that many
+	//strokes of closed shapes are drawn on their inside, except the straight edges of sectors which are centered on them
+
+	fn line(&mut self, from: Vector2, to: Vector2, color: &TP, style: &StrokeStyle);
+	fn polyline(&mut self, points: &[Vector2], color: &TP, style: &StrokeStyle) -> Result<(), RasterError>;
+	fn arc(&mut self, from: Vector2, to: Vector2, start: f32, end: f32, color: &TP, thickness: f32, round_caps: bool);
 
 	fn rect(&mut self, from: Vector2, to: Vector2, color: &TP, roundness: i32);
+	fn rect_stroke(&mut self, from: Vector2, to: Vector2, color: &TP, roundness: i32, thickness: f32);
+	fn ellipse(&mut self, from: Vector2, to: Vector2, sector: Option<(f32, f32)>, color: &TP);
+	fn ellipse_stroke(&mut self, from: Vector2, to: Vector2, sector: Option<(f32, f32)>, color: &TP, thickness: f32);
 	fn circle(&mut self, origin: Vector2, radius: i32, color: &TP);
+	fn circle_stroke(&mut self, origin: Vector2, radius: i32, color: &TP, thickness: f32);
 
 	fn triangle(&mut self, points: [Vector2; 3], color: &TP);
-	fn poly(&mut self, points: &[Vector2], color: &TP);
-
-	fn copy<B: Buffer<Format=TP>>(&mut self, from: Vector2, to: Vector2, buf: &B);
-	fn copy_transform<B: Buffer<Format=TP>>(&mut self, pos: Vector2, scale: Vector2f, origin: Vector2, angle: f32, buf: &B);
+	fn triangle_stroke(&mut self, points: [Vector2; 3], color: &TP, thickness: f32);
+	fn poly(&mut self, points: &[Vector2], rule: FillRule, color: &TP) -> Result<(), RasterError>;
+	fn poly_stroke(&mut self, points: &[Vector2], color: &TP, thickness: f32) -> Result<(), RasterError>;
+
+	fn fill_path<const N: usize>(&mut self, path: &Path<N>, rule: FillRule, color: &TP) -> Result<(), RasterError>;
+	fn stroke_path<const N: usize>(&mut self, path: &Path<N>, color: &TP, style: &StrokeStyle) -> Result<(), RasterError>;
+
+	fn copy<B: Buffer<Format=TP>>(&mut self, from: Vector2, to: Vector2, buf: &B, filter: Filter);
+	//copies the src part of buf, so sprites can be drawn straight from a sheet
+	fn copy_rect<B: Buffer<Format=TP>>(&mut self, from: Vector2, to: Vector2, buf: &B, src: Region, filter: Filter);
+	//draws buf scaled, skewed, then rotated by angle around origin, which is placed at pos
+	//skew.x moves x along for each step in y, skew.y moves y along for each step in x
+	fn copy_transform<B: Buffer<Format=TP>>(&mut self, pos: Vector2, scale: Vector2f, origin: Vector2, angle: f32, skew: Vector2f, buf: &B, filter: Filter);
 	fn text<F: FontBuffer>(&mut self, txt: &DrawText<F>, from: Vector2, to: Vector2, color: &TP) where u8: ToPixel<TP>;
 }
 
@@ -460,6 +1104,10 @@ impl<S: Buffer + Sized> DrawingConvert for S {
 	fn with_mask<'a, B: Buffer>(&'a mut self, mask: &'a B) -> DrawMask<'a, B, Self> {
 		DrawMask { mask, draw: self }
 	}
+
+	fn with_blend<'a>(&'a mut self, mode: BlendMode) -> DrawBlend<'a, Self> {
+		DrawBlend { mode, draw: self }
+	}
 }
 
 impl<S: Buffer> Bounded for S {
@@ -484,300 +1132,374 @@ impl<S: Buffer> Bounded for S {
 	// }
 }
 
-fn corners<B: Buffer + WriteBuffer, P: ToPixel<B::Format>, F: FnMut(i32, i32, P)>(mut blend: F, radius: i32, color: &P) {
-	let area = radius*radius;
-	let halfway = radius/2;
+fn fill_outline<B: Buffer + WriteBuffer, P: ToPixel<B::Format>, O: Outline + ?Sized>(buf: &mut B, outline: &O, rule: FillRule, color: &P) -> Result<(), RasterError> {
+	let clip = buf.clip();
+
+	raster::rasterize(outline, rule, clip.from, clip.to, |x, y, len, cov| {
+		buf.coverage_span(x, y, len, color, cov);
+	})
+}
+
+//buf is scaled, then skewed, then rotated around origin, which lands on pos
+//a step along a row or column of buf moves by axis_x or axis_y on the target
+fn transform_axes(scale: Vector2f, angle: f32, skew: Vector2f) -> (Vector2f, Vector2f) {
+	let rotate = |v: Vector2f| vec2f(cos(angle)*v.x - sin(angle)*v.y, sin(angle)*v.x + cos(angle)*v.y);
+	(rotate(vec2f(scale.x, skew.y*scale.x)), rotate(vec2f(skew.x*scale.y, scale.y)))
+}
+
+//every pixel copy_transform can change drawing a buffer of size with the same arguments
+pub fn transform_bounds(pos: Vector2, scale: Vector2f, origin: Vector2, angle: f32, skew: Vector2f, size: Vector2) -> Region {
+	let (axis_x, axis_y) = transform_axes(scale, angle, skew);
+	let (posf, originf): (Vector2f, Vector2f) = (pos.into(), origin.into());
+	let (w, h) = (size.x as f32, size.y as f32);
 
-	let mut offset = None;
-	fn calc_offset(x: i32, y2: i32, area: i32) -> f32 {
-		0.75 / sqrt(((x*x+y2) - area) as f32)
+	let corners = [vec2f(0.0, 0.0), vec2f(w, 0.0), vec2f(0.0, h), vec2f(w, h)];
+	let place = |corner: Vector2f| {
+		let c = corner - originf;
+		posf + axis_x*c.x + axis_y*c.y
+	};
+
+	//pos itself is only inside when origin is
+	let (mut min, mut max) = (place(corners[0]), place(corners[0]));
+	for &corner in &corners[1..] {
+		let p = place(corner);
+
+		min = vec2f(min.x.min(p.x), min.y.min(p.y));
+		max = vec2f(max.x.max(p.x), max.y.max(p.y));
 	}
 
-	for y in 0..radius {
-		let y2 = y*y;
+	//filtered edges fade out over the pixels just outside
+	Region::new(vec2(floor(min.x) as i32 - 1, floor(min.y) as i32 - 1), vec2(ceil(max.x) as i32 + 1, ceil(max.y) as i32 + 1))
+}
+
+//for the fixed shapes, which only ever have a few edges through any pixel
+//their paths fit what they are built in, and curves are only split into many segments when they are large,
+//so neither error of fill_outline can happen here and it is ignored
+fn fill_shape<B: Buffer + WriteBuffer, P: ToPixel<B::Format>, O: Outline + ?Sized>(buf: &mut B, outline: &O, color: &P) {
+	let _ = fill_outline(buf, outline, FillRule::NonZero, color);
+}
 
-		let mut last_x = true;
-		
-		for x in 0..radius {
-			if (x*x+y2) <= area {
-				blend(x, y, color.clone());
-			} else if last_x {
-				if let Some(off) = offset {
-					blend(x, y, color.clone().mult(off as f32 / 1.5));
-
-					last_x = false;
-
-					if y > halfway {
-						offset = None;
-					} else {
-						offset = Some(calc_offset(x, y2, area));
-					}
-				} else {
-					let off = calc_offset(x, y2, area);
-					blend(x, y, color.clone().mult(off as f32));
-					
-					offset = Some(off);
-				}
-			}
+//buf at u, v in 16.16 fixed point, bilinear reads neighbours up to the edges of src
+fn sample<B: Buffer>(buf: &B, src: &Region, u: i64, v: i64, filter: Filter) -> B::Format {
+	match filter {
+		Filter::Nearest => buf.get_pixel((u >> 16) as i32, (v >> 16) as i32),
+		Filter::Bilinear => {
+			//pixel centers are at .5
+			let (u, v) = (u - 0x8000, v - 0x8000);
+			let (x, y) = ((u >> 16) as i32, (v >> 16) as i32);
+			let (fx, fy) = ((u & 0xffff) as f32 / 65536.0, (v & 0xffff) as f32 / 65536.0);
+
+			let (x0, x1) = (x.max(src.from.x).min(src.to.x - 1), (x + 1).max(src.from.x).min(src.to.x - 1));
+			let (y0, y1) = (y.max(src.from.y).min(src.to.y - 1), (y + 1).max(src.from.y).min(src.to.y - 1));
+
+			let top = buf.get_pixel(x0, y0).choose(buf.get_pixel(x1, y0), 1.0 - fx);
+			let bottom = buf.get_pixel(x0, y1).choose(buf.get_pixel(x1, y1), 1.0 - fx);
+
+			top.choose(bottom, 1.0 - fy)
 		}
 	}
 }
 
+//whether p drawn with mode just replaces what is under it
+fn opaque<P: Pixel>(mode: BlendMode, p: &P) -> bool {
+	mode == BlendMode::Replace || (mode == BlendMode::SrcOver && !p.soft())
+}
+
+//blends a pixel already known to be inside the clip
+fn put<B: Buffer + WriteBuffer, P: ToPixel<B::Format>>(buf: &mut B, x: i32, y: i32, p: P, mode: BlendMode) {
+	if opaque(mode, &p) {
+		buf.set_pixel(x, y, p.to_pixel());
+	} else {
+		let alpha = p.alpha();
+		let px = mode.apply(p.to_pixel(), alpha, buf.get_pixel(x, y));
+
+		buf.set_pixel(x, y, px);
+	}
+}
+
+//blends a pixel only partly covered by a shape, the result fades towards what was there by the uncovered part
+fn put_covered<B: Buffer + WriteBuffer, P: ToPixel<B::Format>>(buf: &mut B, x: i32, y: i32, p: P, mode: BlendMode, coverage: f32) {
+	let (alpha, dst) = (p.alpha(), buf.get_pixel(x, y));
+	let px = mode.apply(p.to_pixel(), alpha, dst.clone()).choose(dst, coverage);
+
+	buf.set_pixel(x, y, px);
+}
+
+//the ellipse bounded by from and to shrunk by inset, or the sector of it from start to end
+fn ellipse_arc(from: Vector2, to: Vector2, inset: f32, sector: Option<(f32, f32)>) -> EllipseArc {
+	let radius: Vector2f = Into::<Vector2f>::into(to - from) / 2.0;
+	let (start, end) = sector.unwrap_or((0.0, 2.0 * PI));
+
+	EllipseArc {
+		center: Into::<Vector2f>::into(from) + radius, radius: radius - inset,
+		start, end, sector: sector.is_some()
+	}
+}
+
 impl<S: Buffer + WriteBuffer, TP: ToPixel<S::Format>> Drawing<S::Format, TP> for S {
 	fn blend(&mut self, x: i32, y: i32, color: TP) {
-		if color.soft() {
-			let t = color.soft_blend();
-			let px = color.to_pixel().choose(self.get_pixel(x, y), t);
-			
-			self.set_pixel(x, y, px);
-		} else {
-			self.set_pixel(x, y, color.to_pixel());
-		}
+		let mode = self.blend_mode();
+		self.blend_with(x, y, color, mode);
 	}
 
-	fn antialiased_blend_x(&mut self, x: f32, y: i32, color: TP) {
-		let xf = x%1.0;
-		
-		self.blend((x-xf) as i32, y, color.clone().mult(1.0-(xf/2.0)));
-		self.blend(ceil(x) as i32, y, color.mult(xf/1.0));
+	fn blend_with(&mut self, x: i32, y: i32, color: TP, mode: BlendMode) {
+		//anything outside the clip is dropped, so shapes can be partly off the target
+		if self.clip().inside(vec2(x, y)) {
+			put(self, x, y, color, mode);
+		}
 	}
 
-	fn antialiased_blend_y(&mut self, x: i32, y: f32, color: TP) {
-		let yf = y%1.0;
-		
-		self.blend(x, (y-yf) as i32, color.clone().mult(1.0-(yf/2.0)));
-		self.blend(x, ceil(y) as i32, color.mult(yf/1.0));
+	fn coverage_blend(&mut self, x: i32, y: i32, color: &TP, coverage: f32) {
+		if coverage >= 1.0 {
+			self.blend(x, y, color.clone());
+		} else if coverage > 0.0 && self.clip().inside(vec2(x, y)) {
+			let mode = self.blend_mode();
+			put_covered(self, x, y, color.clone(), mode, coverage);
+		}
 	}
 
-	fn antialiased_blend_x_dir(&mut self, x: f32, y: i32, right: bool, color: TP) {
-		let xf = x%1.0;
-		
-		if right {
-			self.blend((x-xf) as i32, y, color.clone().mult(1.0));
-			self.blend(ceil(x) as i32, y, color.mult(xf));
-		} else {
-			if xf > 0.0 {
-				self.blend((x-xf) as i32, y, color.clone().mult(1.0-xf));
-			}
+	//like coverage_blend over a span, clipped and handed to the buffer in one go
+	fn coverage_span(&mut self, x: i32, y: i32, len: i32, color: &TP, coverage: f32) {
+		let clip = self.clip();
+		let (from, to) = (x.max(clip.from.x), (x + len).min(clip.to.x));
 
-			self.blend(ceil(x) as i32, y, color.mult(1.0));
+		if y < clip.from.y || y >= clip.to.y || from >= to || coverage <= 0.0 {
+			return;
 		}
-	}
 
-	fn antialiased_blend_y_dir(&mut self, x: i32, y: f32, right: bool, color: TP) {
-		let yf = y%1.0;
-		
-		if right {
-			self.blend(x, (y-yf) as i32, color.clone().mult(1.0));
-			self.blend(x, ceil(y) as i32, color.mult(yf));
-		} else {
-			if yf > 0.0 {
-				self.blend(x, (y-yf) as i32, color.clone().mult(1.0-yf));
+		let mode = self.blend_mode();
+
+		if coverage < 1.0 {
+			//fades towards what is there like coverage_blend, rather than scaling the color
+			for x in from..to {
+				put_covered(self, x, y, color.clone(), mode, coverage);
 			}
-			
-			self.blend(x, ceil(y) as i32, color.mult(1.0));
+		} else if opaque(mode, color) {
+			self.fill_span(from, y, to - from, color.clone().to_pixel());
+		} else {
+			let alpha = color.alpha();
+			self.blend_span(from, y, to - from, color.clone().to_pixel(), alpha);
 		}
 	}
 
-	fn antialiased_blend(&mut self, x: f32, y: f32, color: TP) {
-		let (xf, yf) = (x%1.0, y%1.0);   
-		//set floor coordinate
-		self.blend((x-xf) as i32, (y-yf) as i32, color.clone().mult(1.0-xf-yf));
-		//set ceil coordinate
-		self.blend(ceil(x) as i32, ceil(y) as i32, color.mult((xf+yf)/2.0));
+	fn line(&mut self, from: Vector2, to: Vector2, color: &TP, style: &StrokeStyle) {
+		fill_shape(self, &Polyline {points: &[from, to], style: *style}, color);
 	}
 
-	fn line(&mut self, mut from: Vector2, mut to: Vector2, color: &TP, thickness: i32) {
-		let swapped = (to.y - from.y) > (to.x - from.x);
-		
-		if swapped {
-			mem::swap(&mut from.x, &mut from.y);
-			mem::swap(&mut to.x, &mut to.y);
+	fn polyline(&mut self, points: &[Vector2], color: &TP, style: &StrokeStyle) -> Result<(), RasterError> {
+		fill_outline(self, &Polyline {points, style: *style}, FillRule::NonZero, color)
+	}
+
+	fn arc(&mut self, from: Vector2, to: Vector2, start: f32, end: f32, color: &TP, thickness: f32, round_caps: bool) {
+		//the arc is drawn inside the ellipse bounded by from and to
+		let arc = EllipseArc {sector: false, ..ellipse_arc(from, to, thickness / 2.0, Some((start, end)))};
+		let cap = if round_caps { LineCap::Round } else { LineCap::Butt };
+
+		fill_shape(self, &arc.stroke(StrokeStyle::new(thickness).cap(cap)), color);
+	}
+
+	fn rect(&mut self, from: Vector2, to: Vector2, color: &TP, roundness: i32) {
+		fill_shape(self, &Path::<10>::rounded_rect(from.into(), to.into(), roundness as f32), color);
+	}
+
+	fn ellipse(&mut self, from: Vector2, to: Vector2, sector: Option<(f32, f32)>, color: &TP) {
+		fill_shape(self, &ellipse_arc(from, to, 0.0, sector), color);
+	}
+
+	fn ellipse_stroke(&mut self, from: Vector2, to: Vector2, sector: Option<(f32, f32)>, color: &TP, thickness: f32) {
+		let size = to - from;
+
+		//nothing would be left inside
+		if 2.0 * thickness >= size.x.min(size.y) as f32 {
+			return self.ellipse(from, to, sector, color);
 		}
 
-		if from.x > to.x {
-			mem::swap(&mut to, &mut from);
+		//centered on the ellipse shrunk by half the thickness, so it reaches out to the ellipse
+		let shape = ellipse_arc(from, to, thickness / 2.0, sector);
+		fill_shape(self, &shape.stroke(StrokeStyle::new(thickness)), color);
+	}
+
+	fn rect_stroke(&mut self, from: Vector2, to: Vector2, color: &TP, roundness: i32, thickness: f32) {
+		let size = to - from;
+
+		if 2.0 * thickness >= size.x.min(size.y) as f32 {
+			return self.rect(from, to, color, roundness);
 		}
 
-		let xlen = (to.x - from.x) as f32;
-		let ylen = (to.y - from.y) as f32;
-		let slope = ylen / xlen;
+		//like ellipse_stroke, with the corners mitered or rounded out to the rectangle
+		let (from, to) = (Into::<Vector2f>::into(from) + thickness / 2.0, Into::<Vector2f>::into(to) - thickness / 2.0);
+		let shape = Path::<10>::rounded_rect(from, to, roundness as f32 - thickness / 2.0);
 
-		let mut y = from.y as f32;
-		for x in from.x..to.x {
-			for thick_x in 0..thickness+1 {
-				if (!swapped && y as i32 + thick_x < self.width()) || (swapped && y as i32 + thick_x < self.height()) {
-					if thick_x > 0 && thick_x < thickness {
-						if swapped {
-							self.blend(floor(y) as i32 + thick_x, x, color.clone());
-							self.blend(ceil(y) as i32 + thick_x, x, color.clone());
-						} else {
-							self.blend(x, floor(y) as i32 + thick_x, color.clone());
-							self.blend(x, ceil(y) as i32 + thick_x, color.clone());
-						}
-					} else {
-						if swapped {
-							self.antialiased_blend_x(y + thick_x as f32, x, color.clone());
-						} else {
-							self.antialiased_blend_y(x, y + thick_x as f32, color.clone());
-						}
-					}
-				}
-			}
+		fill_shape(self, &shape.stroke(StrokeStyle::new(thickness)), color);
+	}
+
+	fn circle_stroke(&mut self, origin: Vector2, radius: i32, color: &TP, thickness: f32) {
+		self.ellipse_stroke(origin - radius, origin + radius, None, color, thickness);
+	}
+
+	fn circle(&mut self, origin: Vector2, radius: i32, color: &TP) {
+		self.ellipse(origin - radius, origin + radius, None, color);
+	}
 
-			y += slope;
+	fn triangle(&mut self, points: [Vector2; 3], color: &TP) {
+		fill_shape(self, &points[..], color);
+	}
+
+	fn triangle_stroke(&mut self, points: [Vector2; 3], color: &TP, thickness: f32) {
+		fill_shape(self, &InnerStroke {points: &points, width: thickness}, color);
+	}
+
+	fn poly_stroke(&mut self, points: &[Vector2], color: &TP, thickness: f32) -> Result<(), RasterError> {
+		//two points have no inside to draw on, and closing them would go back over the same line
+		if points.len() == 2 {
+			self.line(points[0], points[1], color, &StrokeStyle::new(thickness));
+			return Ok(());
 		}
+
+		fill_outline(self, &InnerStroke {points, width: thickness}, FillRule::NonZero, color)
 	}
 
-	fn rect(&mut self, from: Vector2, to: Vector2, color: &TP, roundness: i32) {
-		fn simple_rect<B: Buffer + WriteBuffer, P: ToPixel<B::Format>>(buf: &mut B, from: Vector2, to: Vector2, color: &P) {
-			for y in from.y..to.y {
-				for x in from.x..to.x {
-					buf.blend(x, y, color.clone());
-				}
-			}
+	fn poly(&mut self, points: &[Vector2], rule: FillRule, color: &TP) -> Result<(), RasterError> {
+		fill_outline(self, points, rule, color)
+	}
+
+	fn fill_path<const N: usize>(&mut self, path: &Path<N>, rule: FillRule, color: &TP) -> Result<(), RasterError> {
+		if path.is_full() {
+			return Err(RasterError::PathFull);
 		}
-		
-		if roundness > 0 {
-			let tl = from + roundness;
-			let br = to - roundness;
-
-			let bl = vec2(tl.x, br.y);
-			let tr = vec2(br.x, tl.y);
-
-			corners::<Self, TP, _>(|x, y, color| {
-				self.blend(tl.x-x-1, tl.y-y-1, color.clone());
-				self.blend(tr.x+x, tr.y-y-1, color.clone());
-				self.blend(bl.x-x-1, bl.y+y, color.clone());
-				self.blend(br.x+x, br.y+y, color);
-			}, roundness, color);
-			
-			//vertical
-			simple_rect(self, vec2(tl.x, from.y), tr, color);
-			simple_rect(self, bl, vec2(br.x, to.y), color);
-			//horiziontal
-			simple_rect(self, vec2(from.x, tl.y), bl, color);
-			simple_rect(self, tr, vec2(to.x, br.y), color);
-			//middle
-			simple_rect(self, tl, br, color);
-		} else {
-			simple_rect(self, from, to, color);
+
+		fill_outline(self, path, rule, color)
+	}
+
+	fn stroke_path<const N: usize>(&mut self, path: &Path<N>, color: &TP, style: &StrokeStyle) -> Result<(), RasterError> {
+		if path.is_full() {
+			return Err(RasterError::PathFull);
 		}
-		
+
+		fill_outline(self, &path.stroke(*style), FillRule::NonZero, color)
 	}
 
-	fn circle(&mut self, origin: Vector2, radius: i32, color: &TP) {
-		corners::<Self, TP, _>(|x, y, color| {
-			self.blend(origin.x-x-1, origin.y-y-1, color.clone());
-			self.blend(origin.x-x-1, origin.y+y, color.clone());
-			self.blend(origin.x+x, origin.y-y-1, color.clone());
-			self.blend(origin.x+x, origin.y+y, color);
-		}, radius, color);
+	fn copy<B: Buffer<Format=TP>>(&mut self, from: Vector2, to: Vector2, buf: &B, filter: Filter) {
+		self.copy_rect(from, to, buf, Region::new(vec2(0, 0), vec2(buf.width(), buf.height())), filter);
 	}
 
-	fn triangle(&mut self, mut points: [Vector2; 3], color: &TP) {
-		points.sort_unstable_by(|a, b| a.y.cmp(&b.y));
+	fn copy_rect<B: Buffer<Format=TP>>(&mut self, from: Vector2, to: Vector2, buf: &B, src: Region, filter: Filter) {
+		let (size, length) = (src.to - src.from, to - from);
+		if size.x <= 0 || size.y <= 0 || length.x <= 0 || length.y <= 0 {
+			return;
+		}
 
-		let mut flat_triangle = |ult: Vector2, mut left: Vector2, mut right: Vector2, top: bool| {
-				
-			if left.x > right.x {
-				mem::swap(&mut left, &mut right);
-			}
+		//whatever is cut from src is cut from the same part of from..to, so the rest still lines up
+		//the first pixel kept is the first whose center is inside, the last the last that is
+		let inside = src.intersect(Region::new(vec2(0, 0), vec2(buf.width(), buf.height())));
+		let (cut_from, cut_to) = (inside.from - src.from, inside.to - src.from);
+		let kept = Region::new(
+			vec2(from.x + (cut_from.x*length.x + size.x - 1) / size.x, from.y + (cut_from.y*length.y + size.y - 1) / size.y),
+			vec2(from.x + cut_to.x*length.x / size.x, from.y + cut_to.y*length.y / size.y));
+
+		let clip = self.clip().intersect(kept);
+		if inside.is_empty() || clip.is_empty() {
+			return;
+		}
 
-			let left_slope = (left.x - ult.x) as f32 / (left.y - ult.y) as f32;
-			let right_slope = (right.x - ult.x) as f32 / (right.y - ult.y) as f32;
-			
-			let mut left_x = left.x as f32;
-			let mut right_x = right.x as f32;
-
-			self.blend(ult.x, ult.y, color.clone());
-			
-			if top {
-				for y in left.y..ult.y {
-					self.antialiased_blend_x_dir(left_x, y, false, color.clone());
-					self.antialiased_blend_x_dir(right_x, y, true, color.clone());
-					
-					for x in ceil(left_x) as i32+1..floor(right_x) as i32 {
-						self.blend(x, y, color.clone());
-					}
+		let mode = self.blend_mode();
 
-					left_x += left_slope;
-					right_x += right_slope;
-				}
-			} else {
-				for y in (ult.y+1..left.y).into_iter().rev() {
-					self.antialiased_blend_x_dir(right_x, y, true, color.clone());
-					self.antialiased_blend_x_dir(left_x, y, false, color.clone());
-					
-					for x in ceil(left_x) as i32+1..floor(right_x) as i32 {
-						self.blend(x, y, color.clone());
+		if size == length {
+			let (sx, w) = ((src.from.x + clip.from.x - from.x) as usize, (clip.to.x - clip.from.x) as usize);
+
+			for y in clip.from.y..clip.to.y {
+				let sy = src.from.y + y - from.y;
+
+				match buf.get_row(sy).map(|row| &row[sx..sx + w]) {
+					//rows with nothing to blend go straight across
+					Some(row) if row.iter().all(|p| opaque(mode, p)) => self.copy_row(clip.from.x, y, row),
+					_ => for x in clip.from.x..clip.to.x {
+						put(self, x, y, buf.get_pixel(sx as i32 + x - clip.from.x, sy), mode);
 					}
-					
-					left_x -= left_slope;
-					right_x -= right_slope;
 				}
 			}
-		};
-
-		if points[0].y == points[1].y {
-			flat_triangle(points[2], points[0], points[1], true);
-		} else if points[1].y == points[2].y {
-			flat_triangle(points[0], points[1], points[2], false);
 		} else {
-			//hard math you can find it here http://www.sunshine2k.de/coding/java/TriangleRasterization/TriangleRasterization.html
-			let mid = vec2(points[0].x + (((points[1].y - points[0].y) as f32 / (points[2].y - points[0].y) as f32) * (points[2].x - points[0].x) as f32) as i32, points[1].y);
-			
-			flat_triangle(points[0], mid, points[1], false);
-			flat_triangle(points[2], mid, points[1], true);
-		}
-	}
+			//stepping through the source in 16.16 fixed point from pixel centers
+			let step_x = ((size.x as i64) << 16) / length.x as i64;
+			let step_y = ((size.y as i64) << 16) / length.y as i64;
 
-	fn poly(&mut self, points: &[Vector2], color: &TP) {
-		for p1 in 0..points.len() {
-			if p1 > 0 {
-				let p2 = points[p1 - 1];
-				
-				if points.len() > p1 {
-					self.triangle([points[p1], p2, points[p1+1]], color);
-				} else {
-					self.triangle([points[p1], points[0], p2], color);
+			for y in clip.from.y..clip.to.y {
+				let sy = ((src.from.y as i64) << 16) + (y - from.y) as i64*step_y + step_y/2;
+				let mut sx = ((src.from.x as i64) << 16) + (clip.from.x - from.x) as i64*step_x + step_x/2;
+
+				for x in clip.from.x..clip.to.x {
+					put(self, x, y, sample(buf, &inside, sx, sy, filter), mode);
+					sx += step_x;
 				}
-			} else {
-				self.triangle([points[p1], points[points.len()-1], points[p1+1]], color);
 			}
 		}
 	}
 
-	fn copy<B: Buffer<Format=TP>>(&mut self, from: Vector2, to: Vector2, buf: &B) {
-		let length = to - from;
+	fn copy_transform<B: Buffer<Format=TP>>(&mut self, pos: Vector2, scale: Vector2f, origin: Vector2, angle: f32, skew: Vector2f, buf: &B, filter: Filter) {
+		let (axis_x, axis_y) = transform_axes(scale, angle, skew);
 
-		let scale_x = buf.width() as f32 / length.x as f32;
-		let scale_y = buf.height() as f32 / length.y as f32;
-		
-		for y in from.y..to.y {
-			for x in from.x..to.x {
-				let px = buf.get_pixel(((x - from.x) as f32 * scale_x) as i32,
-					((y - from.y) as f32 * scale_y) as i32);
-				
-				self.blend(x, y, px)
-			}
+		let det = axis_x.cross(axis_y);
+		if abs(det) < 1e-6 {
+			return;
 		}
-	}
 
-	fn copy_transform<B: Buffer<Format=TP>>(&mut self, pos: Vector2, scale: Vector2f, origin: Vector2, angle: f32, buf: &B) {
-		for x in 0..self.width() {
-			for y in 0..self.height() {
-				let (fx, fy) = ((x - pos.x + origin.x) as f32 / scale.x, (y - pos.x + origin.y) as f32 / scale.y);
+		let posf: Vector2f = pos.into();
+		let originf: Vector2f = origin.into();
+		let (w, h) = (buf.width() as f32, buf.height() as f32);
 
-				let rx = cos(angle)*fx - sin(angle)*fy;
- 				let ry = sin(angle)*fx + cos(angle)*fy;
+		//only the box around the transformed corners is walked
+		let bounds = transform_bounds(pos, scale, origin, angle, skew, vec2(buf.width(), buf.height()));
+		let clip = self.clip().intersect(bounds);
 
-				let pos = vec2(rx as i32 - origin.x + pos.x, ry as i32 - origin.y + pos.y);
-				
-				if buf.inside(pos) {
-					self.blend(x, y, buf.get_pixel(pos.x, pos.y));
+		if clip.is_empty() {
+			return;
+		}
+
+		//where a target pixel center comes from in buf, in 16.16 fixed point
+		let source = |d: Vector2f| originf + vec2f(axis_y.y*d.x - axis_y.x*d.y, axis_x.x*d.y - axis_x.y*d.x) / det;
+		let fixed = |f: f32| (f * 65536.0) as i64;
+
+		let start = source(vec2f(bounds.from.x as f32 + 0.5, bounds.from.y as f32 + 0.5) - posf);
+		let (step_x, step_y) = (source(vec2f(1.0, 0.0)) - originf, source(vec2f(0.0, 1.0)) - originf);
+		let (du_x, dv_x, du_y, dv_y) = (fixed(step_x.x), fixed(step_x.y), fixed(step_y.x), fixed(step_y.y));
+
+		//stepped from the corner of the bounds rather than of the clip, so every pixel samples the same wherever the clip is
+		let skip = clip.from - bounds.from;
+		let mut row_u = fixed(start.x) + skip.x as i64*du_x + skip.y as i64*du_y;
+		let mut row_v = fixed(start.y) + skip.x as i64*dv_x + skip.y as i64*dv_y;
+		let mode = self.blend_mode();
+
+		let src = Region::new(vec2(0, 0), vec2(buf.width(), buf.height()));
+		//target pixels per source pixel across the edges along x and along y
+		let (across_x, across_y) = (abs(det) / axis_y.length(), abs(det) / axis_x.length());
+
+		for y in clip.from.y..clip.to.y {
+			let (mut u, mut v) = (row_u, row_v);
+
+			for x in clip.from.x..clip.to.x {
+				match filter {
+					Filter::Nearest => if src.inside(vec2((u >> 16) as i32, (v >> 16) as i32)) {
+						put(self, x, y, sample(buf, &src, u, v, filter), mode);
+					},
+					Filter::Bilinear => {
+						let (uf, vf) = (u as f32 / 65536.0, v as f32 / 65536.0);
+						let edge = (uf.min(w - uf) * across_x).min(vf.min(h - vf) * across_y);
+						let cov = clamp(edge + 0.5, 0.0, 1.0);
+
+						if cov >= 1.0 {
+							put(self, x, y, sample(buf, &src, u, v, filter), mode);
+						} else if cov > 0.0 {
+							self.coverage_blend(x, y, &sample(buf, &src, u, v, filter), cov);
+						}
+					}
 				}
+
+				u += du_x;
+				v += dv_x;
 			}
+
+			row_u += du_y;
+			row_v += dv_y;
 		}
 	}
 	
@@ -813,10 +1535,393 @@ impl<S: Buffer + WriteBuffer, TP: ToPixel<S::Format>> Drawing<S::Format, TP> for
 					(from, to)
 				};
 				
-				self.copy(from, to, &glyph.with_color(color));
+				//the glyph is how much of each pixel is covered, so edges fade into what is behind
+				let (size, length) = (vec2(glyph.width(), glyph.height()), to - from);
+				let clip = self.clip().intersect(Region::new(from, to));
+
+				for py in clip.from.y..clip.to.y {
+					let sy = ((py - from.y)*2 + 1) * size.y / (length.y*2);
+
+					for px in clip.from.x..clip.to.x {
+						let sx = ((px - from.x)*2 + 1) * size.x / (length.x*2);
+						self.coverage_blend(px, py, color, glyph.get_pixel(sx, sy).alpha());
+					}
+				}
 
 				x += kern_next.and_then(|c2| txt.font.get_kerning(c, *c2)).unwrap_or(0.0) * txt.font_size;
 			}
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn all<P: Pixel + Copy + PartialEq, const W: usize, const H: usize>(fb: &FrameBuffer<P, W, H>, p: P) -> bool {
+		fb.pixels.iter().all(|row| row.iter().all(|&x| x == p))
+	}
+
+	#[test]
+	fn same_color_edges_stay_unchanged() {
+		let white = RGB(255, 255, 255);
+		let mut fb: FrameBuffer<RGB, 32, 32> = FrameBuffer::new(white);
+
+		fb.ellipse(vec2(4, 4), vec2(28, 28), None, &white);
+		fb.arc(vec2(2, 2), vec2(30, 30), 0.3, 2.5, &white, 3.0, true);
+		assert!(all(&fb, white));
+
+		let gray = RGB565::new(120, 120, 120);
+		let mut fb: FrameBuffer<RGB565, 32, 32> = FrameBuffer::new(gray);
+
+		fb.ellipse(vec2(3, 5), vec2(27, 21), Some((0.5, 4.0)), &gray);
+		assert!(all(&fb, gray));
+
+		//shapes filled by spans of partial coverage
+		let mut fb: FrameBuffer<RGB, 32, 32> = FrameBuffer::new(white);
+
+		fb.circle(vec2(16, 16), 11, &white);
+		fb.triangle([vec2(1, 2), vec2(30, 9), vec2(7, 29)], &white);
+		fb.poly(&[vec2(3, 3), vec2(29, 4), vec2(17, 28)], FillRule::NonZero, &white).unwrap();
+		fb.line(vec2(2, 30), vec2(29, 1), &white, &StrokeStyle::new(1.7));
+		assert!(all(&fb, white));
+
+		let mut fb: FrameBuffer<RGB565, 32, 32> = FrameBuffer::new(gray);
+
+		fb.circle(vec2(16, 16), 11, &gray);
+		fb.triangle([vec2(1, 2), vec2(30, 9), vec2(7, 29)], &gray);
+		assert!(all(&fb, gray));
+	}
+
+	#[test]
+	fn two_point_poly_stroke_is_a_line() {
+		let (a, b) = (vec2(3, 4), vec2(20, 12));
+
+		let mut stroke: FrameBuffer<u8, 24, 16> = FrameBuffer::new(0);
+		stroke.poly_stroke(&[a, b], &255, 3.0).unwrap();
+
+		let mut line: FrameBuffer<u8, 24, 16> = FrameBuffer::new(0);
+		line.line(a, b, &255, &StrokeStyle::new(3.0));
+
+		assert_eq!(stroke.pixels, line.pixels);
+		assert!(stroke.get_pixel(11, 8) == 255);
+	}
+
+	#[test]
+	fn blend_mode_formulas() {
+		let (src, dst) = (RGB(255, 128, 0), RGB(128, 128, 128));
+
+		assert_eq!(BlendMode::SrcOver.apply(200u8, 0.5, 100), 150);
+		assert_eq!(BlendMode::SrcOver.apply(src, 1.0, dst), src);
+		assert_eq!(BlendMode::Multiply.apply(src, 1.0, dst), RGB(128, 64, 0));
+		assert_eq!(BlendMode::Screen.apply(src, 1.0, dst), RGB(255, 192, 128));
+		assert_eq!(BlendMode::Additive.apply(RGB(200, 100, 0), 1.0, RGB(100, 100, 100)), RGB(255, 200, 100));
+		assert_eq!(BlendMode::Additive.apply(RGB(200, 100, 0), 0.5, RGB(100, 100, 100)), RGB(200, 150, 100));
+		assert_eq!(BlendMode::DstOut.apply(255u8, 0.25, 200), 150);
+		assert_eq!(BlendMode::Replace.apply(RGBA(1, 2, 3, 0), 0.0, RGBA(9, 9, 9, 255)), RGBA(1, 2, 3, 0));
+
+		//src only shows where dst has alpha, formats without it fall back to over
+		assert_eq!(BlendMode::SrcIn.apply(RGBA(10, 20, 30, 255), 1.0, RGBA(0, 0, 0, 51)), RGBA(10, 20, 30, 51));
+		assert_eq!(BlendMode::SrcIn.apply(src, 1.0, dst), src);
+
+		//a translucent source only goes part of the way
+		assert_eq!(BlendMode::SrcIn.apply(RGBA(10, 20, 30, 255), 0.5, RGBA(0, 0, 0, 51)), RGBA(5, 10, 15, 51));
+		assert_eq!(BlendMode::DstOut.apply(src, 1.0, dst), dst);
+
+		//a mode with a translucent source fades towards dst by the alpha
+		assert_eq!(BlendMode::Multiply.apply(src, 0.5, dst), RGB(128, 96, 64));
+	}
+
+	#[test]
+	fn src_in_edges_are_covered_in_part() {
+		let mut fb: FrameBuffer<RGBA, 2, 1> = FrameBuffer::new(RGBA(0, 0, 0, 255));
+		let color = RGBA(200, 200, 200, 255);
+
+		fb.with_blend(BlendMode::SrcIn).coverage_blend(0, 0, &color, 0.05);
+		fb.with_blend(BlendMode::SrcIn).coverage_blend(1, 0, &color, 1.0);
+
+		assert_eq!(fb.pixels, [[RGBA(10, 10, 10, 255), color]]);
+
+		//and so does a translucent color
+		let mut fb: FrameBuffer<RGBA, 4, 4> = FrameBuffer::new(RGBA(0, 0, 0, 255));
+		fb.with_blend(BlendMode::SrcIn).rect(vec2(0, 0), vec2(2, 4), &color, 0);
+		fb.with_blend(BlendMode::SrcIn).poly(&[vec2(2, 0), vec2(3, 0), vec2(3, 4), vec2(2, 4)], FillRule::NonZero, &RGBA(200, 200, 200, 128)).unwrap();
+
+		assert_eq!((fb.get_pixel(1, 2), fb.get_pixel(3, 2)), (color, RGBA(0, 0, 0, 255)));
+		assert!(fb.get_pixel(2, 2).0 > 90 && fb.get_pixel(2, 2).0 < 110);
+	}
+
+	#[test]
+	fn adapters_blend_with_the_target_mode() {
+		let mask: FrameBuffer<u8, 4, 1> = FrameBuffer { pixels: [[0, 51, 255, 255]] };
+		let red = RGB(255, 0, 0);
+
+		//the mask fades towards what was there, not towards black
+		let mut fb: FrameBuffer<RGB, 4, 1> = FrameBuffer::new(RGB(0, 0, 255));
+		fb.with_mask(&mask).rect(vec2(0, 0), vec2(4, 1), &red, 0);
+		assert_eq!(fb.pixels, [[RGB(0, 0, 255), RGB(51, 0, 204), red, red]]);
+
+		//what is drawn through with_color only says how much to cover
+		let mut fb: FrameBuffer<RGB, 4, 1> = FrameBuffer::new(RGB(0, 0, 255));
+		fb.with_color(&RGBA(255, 0, 0, 255)).copy(vec2(0, 0), vec2(4, 1), &mask, Filter::Nearest);
+		assert_eq!(fb.pixels, [[RGB(0, 0, 255), RGB(51, 0, 204), red, red]]);
+
+		let mut fb: FrameBuffer<RGB, 4, 1> = FrameBuffer::new(RGB(100, 100, 100));
+		fb.with_blend(BlendMode::Multiply).with_color(&RGBA(255, 128, 0, 255)).copy(vec2(0, 0), vec2(4, 1), &mask, Filter::Nearest);
+		assert_eq!(fb.pixels[0][0], RGB(100, 100, 100));
+		assert_eq!(fb.pixels[0][3], RGB(100, 50, 0));
+	}
+
+	#[test]
+	fn rgb565_packs_and_unpacks() {
+		assert_eq!(RGB565::new(255, 255, 255), RGB565(0xffff));
+		assert_eq!(RGB565::new(255, 0, 0), RGB565(0xf800));
+		assert_eq!(RGB565::new(0, 255, 0), RGB565(0x07e0));
+		assert_eq!(RGB565::new(0, 0, 255), RGB565(0x001f));
+
+		//unpacking spreads the bits over the whole channel, so white stays white
+		assert_eq!(RGB565(0xffff).rgb(), RGB(255, 255, 255));
+		assert_eq!(RGB565(0).rgb(), RGB(0, 0, 0));
+		assert_eq!(RGB565(0x8410).rgb(), RGB(132, 130, 132));
+
+		for x in 0..=u16::MAX {
+			let RGB(r, g, b) = RGB565(x).rgb();
+			assert_eq!(RGB565::new(r, g, b), RGB565(x));
+		}
+
+		let c: RGB565 = RGB(200, 100, 50).to_pixel();
+		let RGB(r, g, b) = c.rgb();
+		assert!((r as i32 - 200).abs() <= 8 && (g as i32 - 100).abs() <= 4 && (b as i32 - 50).abs() <= 8);
+	}
+
+	#[test]
+	fn slice_buffers_lend_rows() {
+		let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+
+		let rgb = SliceBuffer::<RGB>::new(&bytes, 2, 2).unwrap();
+		assert_eq!(rgb.get_row(1), Some(&[RGB(7, 8, 9), RGB(10, 11, 12)][..]));
+
+		let gray = SliceBuffer::<u8>::new(&bytes, 4, 3).unwrap();
+		assert_eq!(gray.get_row(2), Some(&[9, 10, 11, 12][..]));
+
+		let mut copy = bytes;
+		let rgba = SliceBufferMut::<RGBA>::new(&mut copy, 1, 3).unwrap();
+		assert_eq!(rgba.get_row(2), Some(&[RGBA(9, 10, 11, 12)][..]));
+
+		//u16 pixels are only lent on little endian platforms, when they are aligned
+		let words = [0x1234u16, 0xabcd, 0];
+		let aligned = unsafe { core::slice::from_raw_parts(words.as_ptr() as *const u8, 6) };
+		let rgb565 = SliceBuffer::<RGB565>::new(&aligned[..4], 2, 1).unwrap();
+
+		if cfg!(target_endian = "little") {
+			assert_eq!(rgb565.get_row(0), Some(&[RGB565(0x1234), RGB565(0xabcd)][..]));
+		} else {
+			assert_eq!(rgb565.get_row(0), None);
+		}
+
+		//rows that can't be borrowed are read a pixel at a time instead
+		let unaligned = SliceBuffer::<RGB565>::new(&aligned[1..5], 2, 1).unwrap();
+		assert_eq!(unaligned.get_row(0), None);
+		assert_eq!(unaligned.get_pixel(0, 0), RGB565::read(&aligned[1..]));
+	}
+
+	#[test]
+	fn clamped_sources_keep_their_place() {
+		let mut sheet: FrameBuffer<u8, 4, 4> = FrameBuffer::new(0);
+		for y in 0..4 {
+			for x in 0..4 {
+				sheet.set_pixel(x, y, (1 + x + y*4) as u8);
+			}
+		}
+
+		//a source hanging off the top left lands where it would if the sheet went on
+		let mut fb: FrameBuffer<u8, 16, 16> = FrameBuffer::new(0);
+		fb.with_blend(BlendMode::Replace).copy_rect(vec2(10, 10), vec2(16, 16), &sheet, Region::new(vec2(-2, -2), vec2(4, 4)), Filter::Nearest);
+
+		assert_eq!((fb.get_pixel(11, 11), fb.get_pixel(12, 12), fb.get_pixel(15, 15), fb.get_pixel(13, 12)), (0, 1, 16, 2));
+
+		//and scaled, stretched by the same amount
+		let mut fb: FrameBuffer<u8, 16, 16> = FrameBuffer::new(0);
+		fb.with_blend(BlendMode::Replace).copy_rect(vec2(0, 0), vec2(12, 8), &sheet, Region::new(vec2(-2, 0), vec2(4, 4)), Filter::Nearest);
+
+		assert_eq!((fb.get_pixel(3, 0), fb.get_pixel(4, 0), fb.get_pixel(5, 1), fb.get_pixel(11, 7)), (0, 1, 1, 16));
+		assert!(fb.pixels[8..].iter().all(|row| row.iter().all(|&p| p == 0)));
+
+		let mut fb: FrameBuffer<u8, 16, 16> = FrameBuffer::new(0);
+		fb.with_blend(BlendMode::Replace).copy_rect(vec2(0, 0), vec2(16, 16), &sheet, Region::new(vec2(5, 5), vec2(9, 9)), Filter::Bilinear);
+		assert!(all(&fb, 0));
+	}
+
+	#[test]
+	fn bounds_hold_only_the_image() {
+		//origin outside the image, so pos isn't covered
+		let bounds = transform_bounds(vec2(20, 20), vec2f(1.0, 1.0), vec2(-10, -10), 0.0, vec2f(0.0, 0.0), vec2(4, 4));
+		assert_eq!((bounds.from, bounds.to), (vec2(29, 29), vec2(35, 35)));
+
+		let bounds = transform_bounds(vec2(20, 20), vec2f(2.0, 1.0), vec2(0, 0), 0.0, vec2f(0.0, 0.0), vec2(4, 4));
+		assert_eq!((bounds.from, bounds.to), (vec2(19, 19), vec2(29, 25)));
+	}
+
+	#[test]
+	fn points_are_pixel_corners() {
+		let mut rect: FrameBuffer<u8, 16, 16> = FrameBuffer::new(0);
+		rect.rect(vec2(2, 3), vec2(10, 8), &255, 0);
+
+		let mut poly: FrameBuffer<u8, 16, 16> = FrameBuffer::new(0);
+		poly.poly(&[vec2(2, 3), vec2(10, 3), vec2(10, 8), vec2(2, 8)], FillRule::NonZero, &255).unwrap();
+
+		assert_eq!(rect.pixels, poly.pixels);
+		assert_eq!((rect.get_pixel(2, 3), rect.get_pixel(9, 7), rect.get_pixel(10, 7), rect.get_pixel(9, 8)), (255, 255, 0, 0));
+	}
+
+	#[test]
+	fn strokes_stay_inside() {
+		//a pixel wide outline along whole pixels is exactly the border
+		let mut fb: FrameBuffer<u8, 16, 16> = FrameBuffer::new(0);
+		fb.rect_stroke(vec2(2, 2), vec2(10, 8), &255, 0, 1.0);
+
+		for y in 0..16 {
+			for x in 0..16 {
+				let border = (x == 2 || x == 9 || y == 2 || y == 7) && (2..10).contains(&x) && (2..8).contains(&y);
+				assert_eq!(fb.get_pixel(x, y), if border { 255 } else { 0 });
+			}
+		}
+
+		let points = [vec2(2, 2), vec2(29, 6), vec2(9, 27)];
+		let mut fill: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		fill.triangle(points, &255);
+
+		let mut stroke: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		stroke.triangle_stroke(points, &255, 2.5);
+
+		//the corners are mitered all the way out, and the middle left empty
+		assert!((0..32).all(|y| (0..32).all(|x| stroke.get_pixel(x, y) as i32 <= fill.get_pixel(x, y) as i32 + 1)));
+		assert_eq!((stroke.get_pixel(28, 6), stroke.get_pixel(13, 12)), (fill.get_pixel(28, 6), 0));
+		assert!(stroke.get_pixel(9, 26) > 0 && stroke.get_pixel(3, 3) > 200);
+
+		let mut circle: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		circle.circle(vec2(16, 16), 12, &255);
+
+		let mut ring: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		ring.circle_stroke(vec2(16, 16), 12, &255, 3.0);
+
+		//the mitered corners of the flattened ring reach a hair past those of the circle
+		assert!((0..32).all(|y| (0..32).all(|x| ring.get_pixel(x, y) as i32 <= circle.get_pixel(x, y) as i32 + 8)));
+		assert_eq!((ring.get_pixel(16, 16), ring.get_pixel(16, 5), ring.get_pixel(16, 9)), (0, 255, 0));
+	}
+
+	#[test]
+	fn sectors_go_clockwise_from_start() {
+		//a quarter from the x axis towards positive y, the bottom right with y down
+		let mut fb: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		fb.ellipse(vec2(4, 4), vec2(28, 28), Some((0.0, PI / 2.0)), &255);
+
+		assert_eq!((fb.get_pixel(20, 20), fb.get_pixel(12, 20), fb.get_pixel(20, 12), fb.get_pixel(12, 12)), (255, 0, 0, 0));
+
+		//a full turn is the whole ellipse
+		let mut full: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		full.ellipse(vec2(4, 4), vec2(28, 28), Some((0.0, 2.0 * PI)), &255);
+
+		let mut ellipse: FrameBuffer<u8, 32, 32> = FrameBuffer::new(0);
+		ellipse.ellipse(vec2(4, 4), vec2(28, 28), None, &255);
+
+		assert_eq!(full.get_pixel(16, 16), 255);
+		assert_eq!(full.pixels, ellipse.pixels);
+	}
+
+	//panics on any pixel outside it, so drawing has to clip everything
+	struct Strict(FrameBuffer<RGBA, 16, 12>);
+
+	impl Strict {
+		fn check(&self, x: i32, y: i32) {
+			assert!(x >= 0 && y >= 0 && x < 16 && y < 12, "({}, {}) is outside the buffer", x, y);
+		}
+	}
+
+	impl Buffer for Strict {
+		type Format = RGBA;
+
+		fn width(&self) -> i32 {
+			16
+		}
+
+		fn height(&self) -> i32 {
+			12
+		}
+
+		fn get_pixel(&self, x: i32, y: i32) -> RGBA {
+			self.check(x, y);
+			self.0.get_pixel(x, y)
+		}
+	}
+
+	impl WriteBuffer for Strict {
+		fn set_pixel(&mut self, x: i32, y: i32, p: RGBA) {
+			self.check(x, y);
+			self.0.set_pixel(x, y, p);
+		}
+	}
+
+	//every glyph is a block
+	struct Blocks;
+
+	impl FontBuffer for Blocks {
+		type Glyph = SliceGlyphBuffer<'static>;
+
+		fn get_char(&self, _c: char) -> Option<Self::Glyph> {
+			let header = FontCharHeader {width: 4, height: 6, left: 0, top: 0, x_advance: 5.0};
+			Some(SliceGlyphBuffer {header, pixels: &[200; 24]})
+		}
+
+		fn get_kerning(&self, _c1: char, _c2: char) -> Option<f32> {
+			Some(0.5)
+		}
+	}
+
+	#[test]
+	fn drawing_off_the_buffer_is_clipped() {
+		let color = RGBA(200, 100, 50, 180);
+		let image: FrameBuffer<RGBA, 8, 8> = FrameBuffer::new(RGBA(10, 20, 30, 200));
+		let path = Path::<8>::new().move_to(vec2f(0.0, 0.0)).cubic_to(vec2f(30.0, -5.0), vec2f(-5.0, 30.0), vec2f(20.0, 20.0)).close();
+		let style = StrokeStyle::new(3.0).cap(LineCap::Round);
+
+		//partly off each side and corner, and entirely past every edge
+		let offsets = [
+			vec2(-10, -8), vec2(10, -8), vec2(-10, 6), vec2(10, 6), vec2(-4, 3), vec2(6, 3),
+			vec2(-60, 0), vec2(60, 0), vec2(0, -60), vec2(0, 60), vec2(-1000, -1000), vec2(1000, 1000)
+		];
+
+		for &o in &offsets {
+			let mut fb = Strict(FrameBuffer::new(RGBA(0, 0, 0, 255)));
+			let points = [o + vec2(-3, -2), o + vec2(18, 1), o + vec2(4, 17), o + vec2(12, 9)];
+			let triangle = [points[0], points[1], points[2]];
+
+			fb.line(o, o + vec2(20, 14), &color, &style);
+			fb.polyline(&points, &color, &style).unwrap();
+			fb.arc(o, o + vec2(20, 16), 0.5, 5.0, &color, 3.0, true);
+			fb.rect(o, o + vec2(20, 14), &color, 4);
+			fb.rect_stroke(o, o + vec2(20, 14), &color, 4, 2.0);
+			fb.circle(o + 6, 9, &color);
+			fb.circle_stroke(o + 6, 9, &color, 2.5);
+			fb.ellipse(o, o + vec2(22, 14), Some((0.3, 4.0)), &color);
+			fb.ellipse_stroke(o, o + vec2(22, 14), None, &color, 2.0);
+			fb.triangle(triangle, &color);
+			fb.triangle_stroke(triangle, &color, 2.0);
+			fb.poly(&points, FillRule::EvenOdd, &color).unwrap();
+			fb.poly_stroke(&points, &color, 2.0).unwrap();
+
+			let moved = Path::<8>::new().move_to(o.into()).line_to((o + vec2(20, 3)).into()).line_to((o + vec2(6, 15)).into()).close();
+			fb.fill_path(&path, FillRule::NonZero, &color).unwrap();
+			fb.fill_path(&moved, FillRule::NonZero, &color).unwrap();
+			fb.stroke_path(&moved, &color, &style).unwrap();
+
+			fb.copy(o, o + vec2(8, 8), &image, Filter::Nearest);
+			fb.copy(o, o + vec2(20, 13), &image, Filter::Bilinear);
+			fb.copy_rect(o, o + vec2(12, 12), &image, Region::new(vec2(-3, 2), vec2(9, 14)), Filter::Bilinear);
+			fb.copy_transform(o, vec2f(2.5, 1.5), vec2(4, 4), 0.7, vec2f(0.2, 0.0), &image, Filter::Bilinear);
+			fb.copy_transform(o, vec2f(1.0, 1.0), vec2(0, 0), 0.0, vec2f(0.0, 0.0), &image, Filter::Nearest);
+
+			fb.text(&DrawText::new(&Blocks, "off\nscreen").font_size(1.5), o, o + vec2(40, 200), &color);
+		}
+	}
+}
@@ -0,0 +1,210 @@
+use super::*;
+use drawing::{Pixel, Buffer, bayer};
+use core::cell::Cell;
+
+//the largest difference between the channels of a and b, from 0 to 1
+fn spread<P: Pixel>(a: &P, b: &P) -> f32 {
+	let max = Cell::new(0);
+
+	a.clone().combine(b.clone(), |x, y| {
+		max.set(max.get().max((x as i32 - y as i32).abs()));
+		x
+	});
+
+	max.get() as f32 / 255.0
+}
+
+//stops are (position, color) with positions from 0 to 1 in ascending order, there must be at least one
+//jitter moves the color by that many steps of its channels, so dithering looks the same however long the gradient is
+fn color_at<P: Pixel>(stops: &[(f32, P)], t: f32, jitter: f32) -> P {
+	let t = clamp(t, 0.0, 1.0);
+
+	for w in stops.windows(2) {
+		let (from, ref a) = w[0];
+		let (to, ref b) = w[1];
+
+		if t <= to {
+			if to <= from || t <= from {
+				return a.clone();
+			}
+
+			let mut u = (t - from) / (to - from);
+			let spread = spread(a, b);
+
+			if spread > 0.0 {
+				u = clamp(u + jitter * P::STEP / spread, 0.0, 1.0);
+			}
+
+			return b.clone().choose(a.clone(), u);
+		}
+	}
+
+	stops[stops.len() - 1].1.clone()
+}
+
+//an ordered pattern from -dither/2 to dither/2, hiding the bands between colors
+fn jitter(dither: f32, x: i32, y: i32) -> f32 {
+	(bayer(x, y) - 0.5) * dither
+}
+
+pub struct LinearGradient<'a, P: Pixel> {
+	pub size: Vector2,
+	pub from: Vector2f,
+	pub to: Vector2f,
+
+	stops: &'a [(f32, P)],
+	//in steps of the channels of P, 1 is enough to hide the bands
+	pub dither: f32
+}
+
+impl<'a, P: Pixel> LinearGradient<'a, P> {
+	//None when there are no stops
+	pub fn new(size: Vector2, from: Vector2f, to: Vector2f, stops: &'a [(f32, P)]) -> Option<Self> {
+		if stops.is_empty() {
+			return None;
+		}
+
+		Some(LinearGradient {
+			size, from, to, stops,
+			dither: 0.0
+		})
+	}
+
+	pub fn stops(&self) -> &'a [(f32, P)] {
+		self.stops
+	}
+
+	pub fn dither(self, dither: f32) -> Self {
+		LinearGradient {dither, ..self}
+	}
+}
+
+impl<'a, P: Pixel> Buffer for LinearGradient<'a, P> {
+	type Format = P;
+
+	fn width(&self) -> i32 {
+		self.size.x
+	}
+
+	fn height(&self) -> i32 {
+		self.size.y
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> P {
+		let dir = self.to - self.from;
+		let p = vec2f(x as f32 + 0.5, y as f32 + 0.5) - self.from;
+
+		let t = p.dot(dir) / dir.dot(dir);
+		color_at(self.stops, t, jitter(self.dither, x, y))
+	}
+}
+
+//stops and dither work like in LinearGradient, going out from center to radius
+pub struct RadialGradient<'a, P: Pixel> {
+	pub size: Vector2,
+	pub center: Vector2f,
+	pub radius: f32,
+
+	stops: &'a [(f32, P)],
+	pub dither: f32
+}
+
+impl<'a, P: Pixel> RadialGradient<'a, P> {
+	pub fn new(size: Vector2, center: Vector2f, radius: f32, stops: &'a [(f32, P)]) -> Option<Self> {
+		if stops.is_empty() {
+			return None;
+		}
+
+		Some(RadialGradient {
+			size, center, radius, stops,
+			dither: 0.0
+		})
+	}
+
+	pub fn stops(&self) -> &'a [(f32, P)] {
+		self.stops
+	}
+
+	pub fn dither(self, dither: f32) -> Self {
+		RadialGradient {dither, ..self}
+	}
+}
+
+impl<'a, P: Pixel> Buffer for RadialGradient<'a, P> {
+	type Format = P;
+
+	fn width(&self) -> i32 {
+		self.size.x
+	}
+
+	fn height(&self) -> i32 {
+		self.size.y
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> P {
+		let p = vec2f(x as f32 + 0.5, y as f32 + 0.5) - self.center;
+
+		let t = p.length() / self.radius;
+		color_at(self.stops, t, jitter(self.dither, x, y))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use drawing::RGB;
+
+	#[test]
+	fn stops_are_interpolated() {
+		let stops = [(0.0, RGB(0, 0, 0)), (0.5, RGB(200, 0, 0)), (1.0, RGB(200, 100, 40))];
+		let g = LinearGradient::new(vec2(100, 1), vec2f(0.0, 0.0), vec2f(100.0, 0.0), &stops).unwrap();
+
+		let red = |x| g.get_pixel(x, 0).0 as i32;
+		assert!(red(0) <= 2);
+		assert!((red(24) - 98).abs() <= 1);
+		assert_eq!(g.get_pixel(49, 0), RGB(198, 0, 0));
+		assert_eq!(g.get_pixel(50, 0).0, 200);
+
+		let RGB(_, green, blue) = g.get_pixel(74, 0);
+		assert!((green as i32 - 49).abs() <= 1 && (blue as i32 - 19).abs() <= 1);
+
+		//positions past the last stop take its color
+		let radial = RadialGradient::new(vec2(8, 8), vec2f(0.0, 0.0), 4.0, &stops).unwrap();
+		assert_eq!(radial.get_pixel(7, 7), RGB(200, 100, 40));
+
+		let single = [(0.3, RGB(1, 2, 3))];
+		let g = LinearGradient::new(vec2(4, 1), vec2f(0.0, 0.0), vec2f(4.0, 0.0), &single).unwrap();
+		assert_eq!(g.get_pixel(0, 0), RGB(1, 2, 3));
+		assert_eq!(g.get_pixel(3, 0), RGB(1, 2, 3));
+	}
+
+	#[test]
+	fn no_stops() {
+		assert!(LinearGradient::<RGB>::new(vec2(4, 4), vec2f(0.0, 0.0), vec2f(4.0, 0.0), &[]).is_none());
+		assert!(RadialGradient::<RGB>::new(vec2(4, 4), vec2f(0.0, 0.0), 4.0, &[]).is_none());
+	}
+
+	#[test]
+	fn dither_is_in_channel_steps() {
+		let stops = [(0.0, 0u8), (1.0, 255u8)];
+
+		//a gradient many times longer than its 256 levels and one much shorter
+		for &length in &[4000.0, 40.0] {
+			let plain = LinearGradient::new(vec2(4000, 4), vec2f(0.0, 0.0), vec2f(length, 0.0), &stops).unwrap();
+			let dithered = LinearGradient::new(vec2(4000, 4), vec2f(0.0, 0.0), vec2f(length, 0.0), &stops).unwrap().dither(1.0);
+
+			let mut changed = 0;
+
+			for y in 0..4 {
+				for x in 0..(length as i32) {
+					let d = (plain.get_pixel(x, y) as i32 - dithered.get_pixel(x, y) as i32).abs();
+
+					assert!(d <= 1);
+					changed += d;
+				}
+			}
+
+			assert!(changed > 0);
+		}
+	}
+}
@@ -1,5 +1,5 @@
 #![no_std]
-#![feature(core_intrinsics, const_fn)]
+#![feature(core_intrinsics)]
 
 pub mod util;
 pub use util::*;
@@ -11,6 +11,16 @@ pub mod region;
 pub use region::{Bounded, Region};
 
 pub mod drawing;
+pub mod asset;
+pub mod raster;
+pub mod path;
+pub mod gradient;
+pub mod packed;
+pub mod compressed;
+pub mod strip;
+pub mod dirty;
+pub mod display_list;
+pub mod canvas;
 pub mod io;
 pub mod anim;
 
@@ -18,7 +28,7 @@ pub fn transmute<T>(b: &[u8]) -> T {
 	unsafe { core::ptr::read(b.as_ptr() as *const T) }
 }
 
-pub trait Platform<Surface: drawing::Buffer<Format=drawing::RGB> + drawing::WriteBuffer> {
+pub trait Platform<Surface: drawing::Buffer + drawing::WriteBuffer> {
 	fn init() -> Self;
 	
 	fn surface(&mut self) -> &mut Surface;
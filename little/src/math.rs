@@ -1,9 +1,9 @@
 use core::intrinsics::*;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vector2 { pub x: i32, pub y: i32 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vector2f { pub x: f32, pub y: f32 }
 
 macro_rules! impl_math {
@@ -36,6 +36,20 @@ macro_rules! impl_math {
 			}
 		}
 
+		impl core::ops::Mul for $name {
+			type Output = Self;
+
+			fn mul(self, other: Self) -> Self {
+				$name {$($field: self.$field * other.$field),*}
+			}
+		}
+		
+		impl core::ops::MulAssign for $name {		
+			fn mul_assign(&mut self, x: Self) {
+				$(self.$field *= x.$field;)*
+			}
+		}
+
 		impl core::ops::Div for $name {
 			type Output = Self;
 
@@ -96,6 +110,20 @@ macro_rules! impl_math_single {
 			}
 		}
 
+		impl core::ops::Mul<$other> for $name {
+			type Output = Self;
+
+			fn mul(self, other: $other) -> Self {
+				$name {$($field: self.$field * other),*}
+			}
+		}
+		
+		impl core::ops::MulAssign<$other> for $name {		
+			fn mul_assign(&mut self, x: $other) {
+				$(self.$field *= x;)*
+			}
+		}
+
 		impl core::ops::Div<$other> for $name {
 			type Output = Self;
 
@@ -144,6 +172,20 @@ impl_math!(Vector2f, x, y);
 impl_math_single!(Vector2, i32, x, y);
 impl_math_single!(Vector2f, f32, x, y);
 
+impl Vector2f {
+	pub fn length(self) -> f32 {
+		sqrt(self.x*self.x + self.y*self.y)
+	}
+
+	pub fn dot(self, other: Self) -> f32 {
+		self.x*other.x + self.y*other.y
+	}
+
+	pub fn cross(self, other: Self) -> f32 {
+		self.x*other.y - self.y*other.x
+	}
+}
+
 pub const fn vec2(x: i32, y: i32) -> Vector2 {
 	Vector2 {x, y}
 }
@@ -169,7 +211,7 @@ pub fn sqrt(f: f32) -> f32 {
 // }
 
 pub fn abs(f: f32) -> f32 {
-	unsafe { fabsf32(f) }
+	f.abs()
 }
 
 pub fn floor(f: f32) -> f32 {
@@ -180,6 +222,16 @@ pub fn ceil(f: f32) -> f32 {
 	unsafe { ceilf32(f) }
 }
 
+pub fn clamp(f: f32, min: f32, max: f32) -> f32 {
+	if f < min {
+		min
+	} else if f > max {
+		max
+	} else {
+		f
+	}
+}
+
 pub fn pow(f: f32, x: f32) -> f32 {
 	unsafe { powf32(f, x) }
 }
@@ -0,0 +1,269 @@
+use super::*;
+use drawing::{Buffer, WriteBuffer, ToPixel, RGB, bayer};
+use core::marker::PhantomData;
+
+//how many bits each pixel takes, pixels are packed from the most significant bit
+pub trait PackedFormat {
+	const BITS: u8;
+
+	fn max_level() -> u32 {
+		(1 << Self::BITS) - 1
+	}
+}
+
+pub struct Mono;
+pub struct Gray2;
+pub struct Gray4;
+
+impl PackedFormat for Mono {
+	const BITS: u8 = 1;
+}
+
+impl PackedFormat for Gray2 {
+	const BITS: u8 = 2;
+}
+
+impl PackedFormat for Gray4 {
+	const BITS: u8 = 4;
+}
+
+//grayscale pixels several to a byte, each row starting on a new byte
+pub struct PackedBuffer<D, F: PackedFormat> {
+	data: D,
+	width: i32,
+	height: i32,
+	format: PhantomData<F>
+}
+
+impl<D: AsRef<[u8]>, F: PackedFormat> PackedBuffer<D, F> {
+	//None when data is shorter than size(width, height)
+	pub fn new(data: D, width: i32, height: i32) -> Option<Self> {
+		if width < 0 || height < 0 || data.as_ref().len() < Self::size(width, height) {
+			return None;
+		}
+
+		Some(PackedBuffer {
+			data, width, height,
+			format: PhantomData
+		})
+	}
+
+	pub fn data(&self) -> &D {
+		&self.data
+	}
+
+	pub fn into_data(self) -> D {
+		self.data
+	}
+}
+
+impl<D, F: PackedFormat> PackedBuffer<D, F> {
+	pub fn stride(width: i32) -> usize {
+		(width as usize * F::BITS as usize + 7) / 8
+	}
+
+	//bytes needed for a buffer of this size
+	pub fn size(width: i32, height: i32) -> usize {
+		Self::stride(width) * height as usize
+	}
+
+	fn position(&self, x: i32, y: i32) -> (usize, u8) {
+		let bit = x as usize * F::BITS as usize;
+		(y as usize * Self::stride(self.width) + bit / 8, 8 - F::BITS - (bit % 8) as u8)
+	}
+
+	pub fn ordered_dither(&mut self) -> OrderedDither<D, F> {
+		OrderedDither {draw: self}
+	}
+
+	//W is how many pixels of error a row keeps, None when the buffer is wider than that
+	pub fn diffusion_dither<const W: usize>(&mut self) -> Option<DiffusionDither<D, F, W>> {
+		if self.width as usize > W {
+			return None;
+		}
+
+		Some(DiffusionDither {
+			draw: self,
+			last: vec2(-1, -1),
+			carry: 0,
+			row: [0; W],
+			next_row: [0; W]
+		})
+	}
+}
+
+impl<D: AsRef<[u8]>, F: PackedFormat> Buffer for PackedBuffer<D, F> {
+	type Format = u8;
+
+	fn width(&self) -> i32 {
+		self.width
+	}
+
+	fn height(&self) -> i32 {
+		self.height
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> u8 {
+		let (i, shift) = self.position(x, y);
+		let level = (self.data.as_ref()[i] >> shift) as u32 & F::max_level();
+
+		(level * 255 / F::max_level()) as u8
+	}
+}
+
+impl<D: AsRef<[u8]> + AsMut<[u8]>, F: PackedFormat> WriteBuffer for PackedBuffer<D, F> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: u8) {
+		let (i, shift) = self.position(x, y);
+		let level = ((p as u32 * F::max_level() + 127) / 255) as u8;
+		let mask = (F::max_level() as u8) << shift;
+
+		let byte = &mut self.data.as_mut()[i];
+		*byte = (*byte & !mask) | (level << shift);
+	}
+}
+
+//maps a gray value plus a threshold from 0 to 1 onto the nearest level below
+fn dither_level<F: PackedFormat>(value: i32, threshold: f32) -> u8 {
+	let max = F::max_level() as f32;
+	let level = floor(value as f32 * max / 255.0 + threshold);
+
+	(clamp(level, 0.0, max) * 255.0 / max) as u8
+}
+
+//draws rgb onto a packed buffer, spreading the error with a bayer pattern
+pub struct OrderedDither<'a, D, F: PackedFormat> {
+	pub draw: &'a mut PackedBuffer<D, F>
+}
+
+impl<'a, D: AsRef<[u8]>, F: PackedFormat> Buffer for OrderedDither<'a, D, F> {
+	type Format = RGB;
+
+	fn width(&self) -> i32 {
+		self.draw.width()
+	}
+
+	fn height(&self) -> i32 {
+		self.draw.height()
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> RGB {
+		self.draw.get_pixel(x, y).to_pixel()
+	}
+}
+
+impl<'a, D: AsRef<[u8]> + AsMut<[u8]>, F: PackedFormat> WriteBuffer for OrderedDither<'a, D, F> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: RGB) {
+		let gray: u8 = p.to_pixel();
+		self.draw.set_pixel(x, y, dither_level::<F>(gray as i32, bayer(x, y)));
+	}
+}
+
+//draws rgb onto a packed buffer with floyd-steinberg error diffusion
+//the error only carries between pixels written left to right, top to bottom, like the scanline rasterizer does
+//pixels written out of that order are rounded on their own
+pub struct DiffusionDither<'a, D, F: PackedFormat, const W: usize> {
+	pub draw: &'a mut PackedBuffer<D, F>,
+
+	last: Vector2,
+	carry: i32,
+	row: [i16; W],
+	next_row: [i16; W]
+}
+
+impl<'a, D, F: PackedFormat, const W: usize> DiffusionDither<'a, D, F, W> {
+	fn error_at(&self, x: i32, y: i32) -> i32 {
+		if y == self.last.y {
+			let carry = if x == self.last.x + 1 { self.carry } else { 0 };
+			carry + self.row.get(x as usize).map_or(0, |&e| e as i32)
+		} else if y == self.last.y + 1 {
+			self.next_row.get(x as usize).map_or(0, |&e| e as i32)
+		} else {
+			0
+		}
+	}
+
+	fn advance(&mut self, y: i32) {
+		if y == self.last.y + 1 {
+			self.row = self.next_row;
+			self.next_row = [0; W];
+		} else if y != self.last.y {
+			self.row = [0; W];
+			self.next_row = [0; W];
+		}
+	}
+
+	fn spread(&mut self, x: i32, error: i32) {
+		self.carry = error * 7 / 16;
+
+		let mut add = |x: i32, e: i32| {
+			if x >= 0 && (x as usize) < W {
+				let e = self.next_row[x as usize] as i32 + e;
+				self.next_row[x as usize] = e as i16;
+			}
+		};
+
+		add(x - 1, error * 3 / 16);
+		add(x, error * 5 / 16);
+		add(x + 1, error / 16);
+	}
+}
+
+impl<'a, D: AsRef<[u8]>, F: PackedFormat, const W: usize> Buffer for DiffusionDither<'a, D, F, W> {
+	type Format = RGB;
+
+	fn width(&self) -> i32 {
+		self.draw.width()
+	}
+
+	fn height(&self) -> i32 {
+		self.draw.height()
+	}
+
+	fn get_pixel(&self, x: i32, y: i32) -> RGB {
+		self.draw.get_pixel(x, y).to_pixel()
+	}
+}
+
+impl<'a, D: AsRef<[u8]> + AsMut<[u8]>, F: PackedFormat, const W: usize> WriteBuffer for DiffusionDither<'a, D, F, W> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: RGB) {
+		let gray: u8 = p.to_pixel();
+		let value = gray as i32 + self.error_at(x, y);
+
+		self.advance(y);
+
+		let out = dither_level::<F>(value, 0.5);
+		self.draw.set_pixel(x, y, out);
+
+		self.spread(x, value - out as i32);
+		self.last = vec2(x, y);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use drawing::Drawing;
+
+	#[test]
+	fn short_data_is_refused() {
+		assert!(PackedBuffer::<_, Gray2>::new([0u8; 7], 13, 2).is_none());
+		assert!(PackedBuffer::<_, Gray2>::new([0u8; 8], 13, 2).is_some());
+		assert!(PackedBuffer::<_, Mono>::new([0u8; 8], -1, 2).is_none());
+	}
+
+	#[test]
+	fn diffusion_covers_wide_rows() {
+		const WIDTH: usize = 300;
+		let mut data = [0u8; WIDTH / 8 * 2 + 2];
+
+		let mut buf = PackedBuffer::<_, Mono>::new(&mut data[..], WIDTH as i32, 2).unwrap();
+		assert!(buf.diffusion_dither::<256>().is_none());
+
+		//half gray comes out as half the pixels lit, all the way along the row
+		buf.diffusion_dither::<WIDTH>().unwrap().rect(vec2(0, 0), vec2(WIDTH as i32, 2), &RGB(128, 128, 128), 0);
+
+		let lit = |from: i32, to: i32| (from..to).flat_map(|x| (0..2).map(move |y| (x, y))).filter(|&(x, y)| buf.get_pixel(x, y) > 0).count();
+		assert!((lit(0, 100) as i32 - 100).abs() <= 2);
+		assert!((lit(200, 300) as i32 - 100).abs() <= 2);
+	}
+}
@@ -0,0 +1,695 @@
+use super::*;
+use raster::Outline;
+use core::f32::consts::PI;
+
+//a good size for most paths, Path<PATH_CAPACITY>
+pub const PATH_CAPACITY: usize = 64;
+//max distance in pixels between a curve and its flattened segments
+pub const DEFAULT_TOLERANCE: f32 = 0.2;
+
+const MAX_CURVE_SEGMENTS: i32 = 64;
+//how far along the tangents a cubic puts its controls to follow a quarter circle
+const KAPPA: f32 = 0.5522848;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+	MoveTo(Vector2f),
+	LineTo(Vector2f),
+	QuadTo(Vector2f, Vector2f),
+	CubicTo(Vector2f, Vector2f, Vector2f),
+	Close
+}
+
+//a path in pixel coordinates, where pixel (x, y) covers x..x+1 and y..y+1
+//holds up to N commands, past that it is marked full and drawing it fails instead of leaving parts out
+#[derive(Clone)]
+pub struct Path<const N: usize> {
+	commands: [PathCommand; N],
+	len: usize,
+	full: bool,
+
+	pub tolerance: f32
+}
+
+enum Flat {
+	Move(Vector2f),
+	Line(Vector2f),
+	Close
+}
+
+//segments to flatten a curve into so it strays at most dd/n^2 from where it should be
+//a circle flattened into n segments strays radius*pi^2/(2n^2) from the curve, the same for a part of it, so circles pass about radius*5
+fn curve_segments(dd: f32, tolerance: f32) -> i32 {
+	(ceil(sqrt(dd / tolerance)) as i32).max(1).min(MAX_CURVE_SEGMENTS)
+}
+
+impl<const N: usize> Path<N> {
+	pub fn new() -> Self {
+		Path {
+			commands: [PathCommand::Close; N],
+			len: 0, full: false, tolerance: DEFAULT_TOLERANCE
+		}
+	}
+
+	fn push(mut self, cmd: PathCommand) -> Self {
+		if self.len < N {
+			self.commands[self.len] = cmd;
+			self.len += 1;
+		} else {
+			self.full = true;
+		}
+
+		self
+	}
+
+	//whether a command didn't fit, so the path is missing some of what was added
+	pub fn is_full(&self) -> bool {
+		self.full
+	}
+
+	pub fn move_to(self, p: Vector2f) -> Self {
+		self.push(PathCommand::MoveTo(p))
+	}
+
+	pub fn line_to(self, p: Vector2f) -> Self {
+		self.push(PathCommand::LineTo(p))
+	}
+
+	pub fn quad_to(self, control: Vector2f, p: Vector2f) -> Self {
+		self.push(PathCommand::QuadTo(control, p))
+	}
+
+	pub fn cubic_to(self, control1: Vector2f, control2: Vector2f, p: Vector2f) -> Self {
+		self.push(PathCommand::CubicTo(control1, control2, p))
+	}
+
+	pub fn close(self) -> Self {
+		self.push(PathCommand::Close)
+	}
+
+	pub fn tolerance(self, tolerance: f32) -> Self {
+		Path {tolerance, ..self}
+	}
+
+	//a quarter turn from p to to, bending around corner
+	fn quarter(self, p: Vector2f, corner: Vector2f, to: Vector2f) -> Self {
+		self.cubic_to(p + (corner - p) * KAPPA, to + (corner - to) * KAPPA, to)
+	}
+
+	//takes 6 commands
+	pub fn ellipse(center: Vector2f, radius: Vector2f) -> Self {
+		let (l, r) = (center.x - radius.x, center.x + radius.x);
+		let (t, b) = (center.y - radius.y, center.y + radius.y);
+
+		Path::new().move_to(vec2f(r, center.y))
+			.quarter(vec2f(r, center.y), vec2f(r, b), vec2f(center.x, b))
+			.quarter(vec2f(center.x, b), vec2f(l, b), vec2f(l, center.y))
+			.quarter(vec2f(l, center.y), vec2f(l, t), vec2f(center.x, t))
+			.quarter(vec2f(center.x, t), vec2f(r, t), vec2f(r, center.y))
+			.close()
+	}
+
+	//the rectangle from..to with corners rounded by radius, takes 10 commands
+	pub fn rounded_rect(from: Vector2f, to: Vector2f, radius: f32) -> Self {
+		let r = radius.min((to.x - from.x) / 2.0).min((to.y - from.y) / 2.0).max(0.0);
+		let (tr, br, bl, tl) = (vec2f(to.x, from.y), to, vec2f(from.x, to.y), from);
+
+		Path::new().move_to(vec2f(from.x + r, from.y))
+			.line_to(vec2f(to.x - r, from.y)).quarter(vec2f(to.x - r, from.y), tr, vec2f(to.x, from.y + r))
+			.line_to(vec2f(to.x, to.y - r)).quarter(vec2f(to.x, to.y - r), br, vec2f(to.x - r, to.y))
+			.line_to(vec2f(from.x + r, to.y)).quarter(vec2f(from.x + r, to.y), bl, vec2f(from.x, to.y - r))
+			.line_to(vec2f(from.x, from.y + r)).quarter(vec2f(from.x, from.y + r), tl, vec2f(from.x + r, from.y))
+			.close()
+	}
+
+	pub fn commands(&self) -> &[PathCommand] {
+		&self.commands[..self.len]
+	}
+
+	pub fn view(&self) -> PathView {
+		PathView {commands: self.commands(), tolerance: self.tolerance}
+	}
+
+	pub fn stroke(&self, style: StrokeStyle) -> Stroke {
+		self.view().stroke(style)
+	}
+}
+
+//the commands of a path borrowed from wherever they are kept, like a display list
+#[derive(Debug, Clone, Copy)]
+pub struct PathView<'a> {
+	pub commands: &'a [PathCommand],
+	pub tolerance: f32
+}
+
+impl<'a> PathView<'a> {
+	pub fn stroke(self, style: StrokeStyle) -> Stroke<'a> {
+		Stroke {path: self, style}
+	}
+
+	//turns curves into line segments
+	fn flatten<F: FnMut(Flat)>(&self, mut f: F) {
+		let mut last = vec2f(0.0, 0.0);
+
+		for cmd in self.commands {
+			match *cmd {
+				PathCommand::MoveTo(p) => {
+					f(Flat::Move(p));
+					last = p;
+				},
+				PathCommand::LineTo(p) => {
+					f(Flat::Line(p));
+					last = p;
+				},
+				PathCommand::QuadTo(c, p) => {
+					let dd = (last - c * 2.0 + p).length();
+					let n = curve_segments(dd / 4.0, self.tolerance);
+
+					for i in 1..=n {
+						let t = i as f32 / n as f32;
+						let mt = 1.0 - t;
+
+						f(Flat::Line(last * (mt * mt) + c * (2.0 * mt * t) + p * (t * t)));
+					}
+
+					last = p;
+				},
+				PathCommand::CubicTo(c1, c2, p) => {
+					//wang's formula for the number of segments
+					let dd = (last - c1 * 2.0 + c2).length().max((c1 - c2 * 2.0 + p).length());
+					let n = curve_segments(dd * 0.75, self.tolerance);
+
+					for i in 1..=n {
+						let t = i as f32 / n as f32;
+						let mt = 1.0 - t;
+
+						f(Flat::Line(last * (mt * mt * mt) + c1 * (3.0 * mt * mt * t) + c2 * (3.0 * mt * t * t) + p * (t * t * t)));
+					}
+
+					last = p;
+				},
+				PathCommand::Close => f(Flat::Close)
+			}
+		}
+	}
+}
+
+impl<const N: usize> Outline for Path<N> {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F) {
+		self.view().edges(f)
+	}
+}
+
+//fills implicitly close every subpath
+impl<'a> Outline for PathView<'a> {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F) {
+		let mut closer = Closer::new(f);
+
+		self.flatten(|ev| closer.event(ev));
+		closer.finish();
+	}
+}
+
+//turns flattened lines into edges, closing each subpath back to where it started
+struct Closer<F: FnMut(Vector2f, Vector2f)> {
+	f: F,
+	start: Option<Vector2f>,
+	last: Vector2f
+}
+
+impl<F: FnMut(Vector2f, Vector2f)> Closer<F> {
+	fn new(f: F) -> Self {
+		Closer {f, start: None, last: vec2f(0.0, 0.0)}
+	}
+
+	fn event(&mut self, ev: Flat) {
+		match ev {
+			Flat::Move(p) => {
+				self.finish();
+
+				self.start = Some(p);
+				self.last = p;
+			},
+			Flat::Line(p) => {
+				(self.f)(self.last, p);
+				self.last = p;
+
+				if self.start.is_none() {
+					self.start = Some(p);
+				}
+			},
+			Flat::Close => {
+				if let Some(start) = self.start {
+					(self.f)(self.last, start);
+					self.last = start;
+				}
+			}
+		}
+	}
+
+	fn finish(&mut self) {
+		if let Some(start) = self.start {
+			(self.f)(self.last, start);
+		}
+	}
+}
+
+//point on the ellipse in the direction of angle
+fn ellipse_point(radius: Vector2f, angle: f32) -> Vector2f {
+	let dir = vec2f(cos(angle), sin(angle));
+	let r = (radius.x * radius.y) / (vec2f(radius.y * dir.x, radius.x * dir.y)).length();
+
+	dir * r
+}
+
+//the part of an ellipse going clockwise from start to end, angles in radians from the x axis
+//a sector also runs through the center so it fills as a slice, otherwise the ends are closed straight
+//from start to a full turn after it is the whole ellipse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EllipseArc {
+	pub center: Vector2f,
+	pub radius: Vector2f,
+	pub start: f32,
+	pub end: f32,
+	pub sector: bool
+}
+
+impl EllipseArc {
+	pub fn stroke(self, style: StrokeStyle) -> ArcStroke {
+		ArcStroke {arc: self, style}
+	}
+
+	//reach is how far past the ellipse the outline goes, so the segments are as fine there as a fill of the bigger ellipse
+	fn flatten<F: FnMut(Flat)>(&self, reach: f32, mut f: F) {
+		let full = self.end - self.start >= 2.0 * PI;
+		let mut sweep = if full { 2.0 * PI } else { (self.end - self.start) % (2.0 * PI) };
+
+		if sweep < 0.0 {
+			sweep += 2.0 * PI;
+		}
+
+		if sweep <= 0.0 || self.radius.x <= 0.0 || self.radius.y <= 0.0 {
+			return;
+		}
+
+		let turn = curve_segments((self.radius.x.max(self.radius.y) + reach) * 5.0, DEFAULT_TOLERANCE);
+		let n = (ceil(turn as f32 * sweep / (2.0 * PI)) as i32).max(1);
+		let point = |i: i32| self.center + ellipse_point(self.radius, self.start + sweep * i as f32 / n as f32);
+
+		if full {
+			f(Flat::Move(point(0)));
+			for i in 1..n {
+				f(Flat::Line(point(i)));
+			}
+
+			f(Flat::Close);
+			return;
+		}
+
+		if self.sector {
+			f(Flat::Move(self.center));
+			f(Flat::Line(point(0)));
+		} else {
+			f(Flat::Move(point(0)));
+		}
+
+		for i in 1..=n {
+			f(Flat::Line(point(i)));
+		}
+
+		if self.sector {
+			f(Flat::Close);
+		}
+	}
+}
+
+impl Outline for EllipseArc {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F) {
+		let mut closer = Closer::new(f);
+
+		self.flatten(0.0, |ev| closer.event(ev));
+		closer.finish();
+	}
+}
+
+//emits a closed polygon, always wound the same way so overlapping pieces union under the non-zero rule
+fn emit_poly<F: FnMut(Vector2f, Vector2f)>(points: &[Vector2f], f: &mut F) {
+	let mut area = 0.0;
+	let mut prev = points[points.len() - 1];
+
+	for &p in points {
+		area += prev.cross(p);
+		prev = p;
+	}
+
+	let n = points.len();
+	for i in 0..n {
+		let (a, b) = (points[i], points[(i + 1) % n]);
+
+		if area > 0.0 {
+			f(b, a);
+		} else {
+			f(a, b);
+		}
+	}
+}
+
+fn emit_circle<F: FnMut(Vector2f, Vector2f)>(center: Vector2f, radius: f32, f: &mut F) {
+	let n = curve_segments(radius * 5.0, DEFAULT_TOLERANCE).max(4) as usize;
+	let mut points = [vec2f(0.0, 0.0); MAX_CURVE_SEGMENTS as usize];
+
+	for i in 0..n {
+		let angle = i as f32 * 2.0 * PI / n as f32;
+		points[i] = center + vec2f(cos(angle), sin(angle)) * radius;
+	}
+
+	emit_poly(&points[..n], f);
+}
+
+fn normal(dir: Vector2f) -> Vector2f {
+	vec2f(-dir.y, dir.x)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineCap {
+	Butt, Round, Square
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineJoin {
+	Miter, Round, Bevel
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+	pub width: f32,
+	pub cap: LineCap,
+	pub join: LineJoin,
+	//longest miter allowed, relative to the width, before falling back to a bevel
+	pub miter_limit: f32
+}
+
+impl StrokeStyle {
+	pub fn new(width: f32) -> Self {
+		StrokeStyle {
+			width, cap: LineCap::Butt, join: LineJoin::Miter,
+			miter_limit: 4.0
+		}
+	}
+
+	pub fn cap(self, cap: LineCap) -> Self {
+		StrokeStyle {cap, ..self}
+	}
+
+	pub fn join(self, join: LineJoin) -> Self {
+		StrokeStyle {join, ..self}
+	}
+
+	pub fn miter_limit(self, miter_limit: f32) -> Self {
+		StrokeStyle {miter_limit, ..self}
+	}
+}
+
+//turns a sequence of lines into the pieces of its stroke
+struct Stroker<'a, F: FnMut(Vector2f, Vector2f)> {
+	style: &'a StrokeStyle,
+	f: F,
+
+	start: Vector2f,
+	last: Vector2f,
+	first_dir: Option<Vector2f>,
+	last_dir: Option<Vector2f>,
+	//a zero length line, which still gets round or square caps
+	dot: bool
+}
+
+impl<'a, F: FnMut(Vector2f, Vector2f)> Stroker<'a, F> {
+	fn new(style: &'a StrokeStyle, f: F) -> Self {
+		Stroker {
+			style, f,
+			start: vec2f(0.0, 0.0), last: vec2f(0.0, 0.0),
+			first_dir: None, last_dir: None, dot: false
+		}
+	}
+
+	fn half_width(&self) -> f32 {
+		self.style.width / 2.0
+	}
+
+	fn cap(&mut self, p: Vector2f, dir: Vector2f) {
+		let hw = self.half_width();
+		let n = normal(dir) * hw;
+
+		match self.style.cap {
+			LineCap::Butt => (),
+			LineCap::Round => emit_circle(p, hw, &mut self.f),
+			LineCap::Square => {
+				let out = p + dir * hw;
+				emit_poly(&[p + n, out + n, out - n, p - n], &mut self.f);
+			}
+		}
+	}
+
+	fn join(&mut self, p: Vector2f, from_dir: Vector2f, to_dir: Vector2f) {
+		let hw = self.half_width();
+		let turn = from_dir.cross(to_dir);
+		let cos_turn = from_dir.dot(to_dir);
+
+		if abs(turn) < 1e-4 && cos_turn > 0.0 {
+			return;
+		}
+
+		//the gap to fill is on the outside of the turn
+		let side = if turn > 0.0 { -1.0 } else { 1.0 };
+		let (n1, n2) = (normal(from_dir) * side, normal(to_dir) * side);
+		let bevel = [p, p + n1 * hw, p + n2 * hw];
+
+		match self.style.join {
+			//flattened curves only turn slightly, where a bevel is as good as round
+			LineJoin::Round if cos_turn < 0.99 => emit_circle(p, hw, &mut self.f),
+			LineJoin::Miter => {
+				//the miter is 1/cos(theta/2) times the width
+				let cos_half = sqrt((1.0 + n1.dot(n2)) / 2.0);
+
+				if cos_half > 0.0 && 1.0 / cos_half <= self.style.miter_limit {
+					let tip = p + (n1 + n2) * (hw / (1.0 + n1.dot(n2)));
+					emit_poly(&[p, p + n1 * hw, tip, p + n2 * hw], &mut self.f);
+				} else {
+					emit_poly(&bevel, &mut self.f);
+				}
+			},
+			_ => emit_poly(&bevel, &mut self.f)
+		}
+	}
+
+	fn finish(&mut self) {
+		match (self.first_dir, self.last_dir) {
+			(Some(first), Some(last)) => {
+				let (start, end) = (self.start, self.last);
+
+				self.cap(start, vec2f(-first.x, -first.y));
+				self.cap(end, last);
+			},
+			_ if self.dot => {
+				let p = self.start;
+
+				self.cap(p, vec2f(1.0, 0.0));
+				self.cap(p, vec2f(-1.0, 0.0));
+			},
+			_ => ()
+		}
+
+		self.first_dir = None;
+		self.last_dir = None;
+		self.dot = false;
+	}
+
+	fn move_to(&mut self, p: Vector2f) {
+		self.finish();
+
+		self.start = p;
+		self.last = p;
+	}
+
+	fn line_to(&mut self, p: Vector2f) {
+		let last = self.last;
+		let len = (p - last).length();
+
+		if len <= 0.0 {
+			self.dot = true;
+			return;
+		}
+
+		let dir = (p - last) / len;
+		let n = normal(dir) * self.half_width();
+
+		emit_poly(&[last + n, p + n, p - n, last - n], &mut self.f);
+
+		match self.last_dir {
+			Some(prev) => self.join(last, prev, dir),
+			None => self.first_dir = Some(dir)
+		}
+
+		self.last_dir = Some(dir);
+		self.last = p;
+	}
+
+	fn event(&mut self, ev: Flat) {
+		match ev {
+			Flat::Move(p) => self.move_to(p),
+			Flat::Line(p) => self.line_to(p),
+			Flat::Close => self.close()
+		}
+	}
+
+	fn close(&mut self) {
+		let start = self.start;
+		self.line_to(start);
+
+		if let (Some(from), Some(to)) = (self.last_dir, self.first_dir) {
+			self.join(start, from, to);
+		}
+
+		self.first_dir = None;
+		self.last_dir = None;
+		self.dot = false;
+	}
+}
+
+//the outline of a path stroked with style, centered on the path
+pub struct Stroke<'a> {
+	pub path: PathView<'a>,
+	pub style: StrokeStyle
+}
+
+impl<'a> Outline for Stroke<'a> {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F) {
+		let mut stroker = Stroker::new(&self.style, f);
+
+		self.path.flatten(|ev| stroker.event(ev));
+		stroker.finish();
+	}
+}
+
+//the outline of an arc stroked with style, centered on the arc
+pub struct ArcStroke {
+	pub arc: EllipseArc,
+	pub style: StrokeStyle
+}
+
+impl Outline for ArcStroke {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F) {
+		let mut stroker = Stroker::new(&self.style, f);
+
+		self.arc.flatten(self.style.width / 2.0, |ev| stroker.event(ev));
+		stroker.finish();
+	}
+}
+
+//the outline of connected lines through points
+pub struct Polyline<'a> {
+	pub points: &'a [Vector2],
+	pub style: StrokeStyle
+}
+
+impl<'a> Outline for Polyline<'a> {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F) {
+		let mut stroker = Stroker::new(&self.style, f);
+
+		if let Some((first, rest)) = self.points.split_first() {
+			stroker.move_to((*first).into());
+
+			for &p in rest {
+				stroker.line_to(p.into());
+			}
+		}
+
+		stroker.finish();
+	}
+}
+
+//the outline of the polygon through points stroked on its inside, so it covers nothing the polygon doesn't
+//polygons with no area have no inside and give nothing
+pub struct InnerStroke<'a> {
+	pub points: &'a [Vector2],
+	pub width: f32
+}
+
+impl<'a> Outline for InnerStroke<'a> {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F) {
+		let points = self.points;
+		let n = points.len();
+		let at = |i: usize| -> Vector2f { points[i % n].into() };
+
+		let mut area = 0.0;
+		for i in 0..n {
+			area += at(i + n - 1).cross(at(i));
+		}
+
+		if area == 0.0 {
+			return;
+		}
+
+		//a closed line through the polygon moved in by half the width, with the corners of the stroke mitered
+		//out to exactly the corners of the polygon
+		let hw = self.width / 2.0;
+		let inward = |a: Vector2f, b: Vector2f| {
+			let dir = (b - a) / (b - a).length();
+			if area > 0.0 { normal(dir) } else { normal(dir) * -1.0 }
+		};
+
+		let style = StrokeStyle::new(self.width).miter_limit(f32::INFINITY);
+		let mut stroker = Stroker::new(&style, f);
+		let mut first = true;
+
+		for i in 0..n {
+			//points repeated one after another are only one corner
+			let (prev, cur) = (at(i + n - 1), at(i));
+			if prev == cur {
+				continue;
+			}
+
+			let next = (1..n).map(|j| at(i + j)).find(|&p| p != cur).unwrap_or(prev);
+			let (n1, n2) = (inward(prev, cur), inward(cur, next));
+			let d = 1.0 + n1.dot(n2);
+
+			let p = if d > 1e-4 { cur + (n1 + n2) * (hw / d) } else { cur + n1 * hw };
+
+			if first {
+				stroker.move_to(p);
+				first = false;
+			} else {
+				stroker.line_to(p);
+			}
+		}
+
+		stroker.close();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use drawing::*;
+	use raster::{FillRule, RasterError};
+
+	#[test]
+	fn full_paths_are_not_drawn() {
+		let square: Path<5> = Path::new().move_to(vec2f(1.0, 1.0)).line_to(vec2f(7.0, 1.0))
+			.line_to(vec2f(7.0, 7.0)).line_to(vec2f(1.0, 7.0)).close();
+		assert!(!square.is_full());
+
+		let mut fb: FrameBuffer<u8, 8, 8> = FrameBuffer::new(0);
+		assert_eq!(fb.fill_path(&square, FillRule::NonZero, &255), Ok(()));
+		assert_eq!(fb.get_pixel(4, 4), 255);
+
+		let more = square.move_to(vec2f(0.0, 0.0));
+		assert!(more.is_full());
+		assert_eq!(more.commands().len(), 5);
+
+		let mut fb: FrameBuffer<u8, 8, 8> = FrameBuffer::new(0);
+		assert_eq!(fb.fill_path(&more, FillRule::NonZero, &255), Err(RasterError::PathFull));
+		assert_eq!(fb.stroke_path(&more, &255, &StrokeStyle::new(1.0)), Err(RasterError::PathFull));
+		assert_eq!(fb.pixels, [[0; 8]; 8]);
+	}
+}
@@ -0,0 +1,471 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillRule {
+	NonZero,
+	EvenOdd
+}
+
+impl FillRule {
+	fn inside(self, winding: i32) -> bool {
+		match self {
+			FillRule::NonZero => winding != 0,
+			FillRule::EvenOdd => winding % 2 != 0
+		}
+	}
+}
+
+//anything made of closed outlines that can be filled
+pub trait Outline {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, f: F);
+}
+
+//points are pixel corners like the points of paths, so pixel (x, y) covers x..x+1 and y..y+1
+impl Outline for [Vector2] {
+	fn edges<F: FnMut(Vector2f, Vector2f)>(&self, mut f: F) {
+		if self.is_empty() {
+			return;
+		}
+
+		let mut prev: Vector2f = self[self.len() - 1].into();
+		for &point in self {
+			let cur: Vector2f = point.into();
+			f(prev, cur);
+			prev = cur;
+		}
+	}
+}
+
+//scanlines sampled per pixel row, horizontal coverage is exact
+pub const SUBSAMPLES: usize = 4;
+//edges kept at once, each takes 20 bytes of stack
+//outlines with more are filled a few rows, or a piece of a row, at a time
+pub const MAX_EDGES: usize = 128;
+//outlines are rasterized in fixed point with this many fractional bits
+pub const FRAC_BITS: i32 = 8;
+
+const ONE: i32 = 1 << FRAC_BITS;
+//coverage of a pixel entirely inside, each sample row adds up to ONE
+const FULL: u16 = (ONE * SUBSAMPLES as i32) as u16;
+const CHUNK: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RasterError {
+	//more than MAX_EDGES edges pass through a single pixel, what was filled before it stays
+	TooManyEdges,
+	//a path that had more commands added than it could hold, nothing of it is drawn
+	PathFull
+}
+
+fn fixed(p: Vector2f) -> Vector2 {
+	vec2(floor(p.x * ONE as f32 + 0.5) as i32, floor(p.y * ONE as f32 + 0.5) as i32)
+}
+
+fn sample_y(y: i32, s: usize) -> i32 {
+	(y << FRAC_BITS) + (2*s as i32 + 1)*ONE / (2*SUBSAMPLES as i32)
+}
+
+//an edge in fixed point as the outline gave it, and where it crosses the sample row being filled
+#[derive(Clone, Copy)]
+struct Edge {
+	a: Vector2,
+	b: Vector2,
+	x: i32
+}
+
+impl Edge {
+	fn top(&self) -> i32 {
+		self.a.y.min(self.b.y)
+	}
+
+	fn bottom(&self) -> i32 {
+		self.a.y.max(self.b.y)
+	}
+
+	fn dir(&self) -> i32 {
+		if self.a.y < self.b.y { 1 } else { -1 }
+	}
+
+	//top-left rule, a sample on the top end of an edge is crossed and one on the bottom end isn't
+	//so a sample on a vertex shared by two edges is only crossed once
+	fn crosses(&self, y: i32) -> bool {
+		y >= self.top() && y < self.bottom()
+	}
+
+	//walked from top to bottom, so edges shared by two shapes cross every row at exactly the same x
+	fn x_at(&self, y: i32) -> i32 {
+		let (a, b) = if self.a.y < self.b.y { (self.a, self.b) } else { (self.b, self.a) };
+		a.x + ((y - a.y) as i64 * (b.x - a.x) as i64 / (b.y - a.y) as i64) as i32
+	}
+}
+
+//the edges of an outline that matter inside area, gathered in one walk over it
+struct Edges {
+	list: [Edge; MAX_EDGES],
+	len: usize,
+	area: Region,
+	//winding of the edges left of area on each sample row, which are only left out when area is one row
+	left: [i32; SUBSAMPLES],
+	//pixels the whole outline touches
+	bounds: Region
+}
+
+impl Edges {
+	fn new() -> Self {
+		let empty = Region::new(vec2(0, 0), vec2(0, 0));
+
+		Edges {
+			list: [Edge {a: vec2(0, 0), b: vec2(0, 0), x: 0}; MAX_EDGES], len: 0,
+			area: empty, left: [0; SUBSAMPLES], bounds: empty
+		}
+	}
+
+	//returns false when more than MAX_EDGES edges reach into area
+	fn collect<O: Outline + ?Sized>(&mut self, outline: &O, area: Region) -> bool {
+		let (top, bottom) = (area.from.y << FRAC_BITS, area.to.y << FRAC_BITS);
+		let (left, right) = (area.from.x << FRAC_BITS, area.to.x << FRAC_BITS);
+		let one_row = area.to.y - area.from.y == 1;
+
+		let (mut min, mut max) = (vec2(i32::MAX, i32::MAX), vec2(i32::MIN, i32::MIN));
+		let mut overflow = false;
+
+		self.len = 0;
+		self.left = [0; SUBSAMPLES];
+
+		let (list, len, winding) = (&mut self.list, &mut self.len, &mut self.left);
+
+		outline.edges(|a, b| {
+			let edge = Edge {a: fixed(a), b: fixed(b), x: 0};
+			let (min_x, max_x) = (edge.a.x.min(edge.b.x), edge.a.x.max(edge.b.x));
+
+			min = vec2(min.x.min(min_x), min.y.min(edge.top()));
+			max = vec2(max.x.max(max_x), max.y.max(edge.bottom()));
+
+			//flat edges cross no samples, and ones above, below or right of area don't change what is inside it
+			if edge.top() == edge.bottom() || edge.bottom() <= top || edge.top() >= bottom || min_x >= right {
+				return;
+			}
+
+			if one_row && max_x <= left {
+				for s in 0..SUBSAMPLES {
+					if edge.crosses(sample_y(area.from.y, s)) {
+						winding[s] += edge.dir();
+					}
+				}
+			} else if *len < MAX_EDGES {
+				list[*len] = edge;
+				*len += 1;
+			} else {
+				overflow = true;
+			}
+		});
+
+		self.area = area;
+		self.bounds = if min.x > max.x {
+			Region::new(vec2(0, 0), vec2(0, 0))
+		} else {
+			Region::new(vec2(min.x >> FRAC_BITS, min.y >> FRAC_BITS),
+				vec2((max.x + ONE - 1) >> FRAC_BITS, (max.y + ONE - 1) >> FRAC_BITS))
+		};
+
+		!overflow
+	}
+
+	//the spans inside the outline on sample row y of the edges list[..active], added to acc from chunk_x
+	fn cover(&mut self, rule: FillRule, active: usize, y: i32, s: usize, chunk: (i32, i32), acc: &mut [u16; CHUNK]) {
+		let edges = &mut self.list[..active];
+		let y = sample_y(y, s);
+
+		for e in edges.iter_mut() {
+			e.x = if e.crosses(y) { e.x_at(y) } else { i32::MAX };
+		}
+
+		//insertion sort, the order hardly changes between sample rows
+		for i in 1..edges.len() {
+			let mut j = i;
+			while j > 0 && edges[j - 1].x > edges[j].x {
+				edges.swap(j - 1, j);
+				j -= 1;
+			}
+		}
+
+		let (from, to) = (chunk.0 << FRAC_BITS, chunk.1 << FRAC_BITS);
+		let mut winding = self.left[s];
+		let mut start = from;
+
+		let mut add = |l: i32, r: i32| {
+			let (l, r) = (l.max(from), r.min(to));
+			if l >= r {
+				return;
+			}
+
+			let (li, ri) = (l >> FRAC_BITS, r >> FRAC_BITS);
+			let (lx, rx) = ((li - chunk.0) as usize, (ri - chunk.0) as usize);
+
+			if li == ri {
+				acc[lx] += (r - l) as u16;
+			} else {
+				acc[lx] += (((li + 1) << FRAC_BITS) - l) as u16;
+
+				for a in &mut acc[lx + 1..rx] {
+					*a += ONE as u16;
+				}
+
+				if rx < CHUNK {
+					acc[rx] += (r - (ri << FRAC_BITS)) as u16;
+				}
+			}
+		};
+
+		for e in edges.iter().take_while(|e| e.x != i32::MAX) {
+			let was_inside = rule.inside(winding);
+			winding += e.dir();
+
+			match (was_inside, rule.inside(winding)) {
+				(false, true) => start = e.x,
+				(true, false) => add(start, e.x),
+				_ => ()
+			}
+		}
+
+		//still inside past the last edge when the edges right of area were left out
+		if rule.inside(winding) {
+			add(start, to);
+		}
+	}
+
+	//calls span for every covered run of pixels in area
+	fn fill<F: FnMut(i32, i32, i32, f32)>(&mut self, rule: FillRule, span: &mut F) {
+		let area = self.area.intersect(self.bounds);
+		let (mut active, mut len) = (0, self.len);
+
+		self.list[..len].sort_unstable_by_key(|e| e.top());
+
+		for y in area.from.y..area.to.y {
+			let (row_top, row_bottom) = (y << FRAC_BITS, (y + 1) << FRAC_BITS);
+
+			//edges ending above this row are dropped and the ones starting in it added
+			let mut kept = 0;
+			for i in 0..active {
+				if self.list[i].bottom() > row_top {
+					self.list[kept] = self.list[i];
+					kept += 1;
+				}
+			}
+
+			self.list.copy_within(active..len, kept);
+			len -= active - kept;
+			active = kept;
+
+			while active < len && self.list[active].top() < row_bottom {
+				active += 1;
+			}
+
+			if active == 0 && self.left.iter().all(|&w| !rule.inside(w)) {
+				continue;
+			}
+
+			let mut chunk_x = area.from.x;
+			while chunk_x < area.to.x {
+				let chunk_end = (chunk_x + CHUNK as i32).min(area.to.x);
+				let mut acc = [0u16; CHUNK];
+
+				for s in 0..SUBSAMPLES {
+					self.cover(rule, active, y, s, (chunk_x, chunk_end), &mut acc);
+				}
+
+				let mut i = 0;
+				let chunk_len = (chunk_end - chunk_x) as usize;
+
+				while i < chunk_len {
+					if acc[i] >= FULL {
+						let start = i;
+						while i < chunk_len && acc[i] >= FULL {
+							i += 1;
+						}
+
+						span(chunk_x + start as i32, y, (i - start) as i32, 1.0);
+					} else {
+						if acc[i] > 0 {
+							span(chunk_x + i as i32, y, 1, acc[i] as f32 / FULL as f32);
+						}
+
+						i += 1;
+					}
+				}
+
+				chunk_x = chunk_end;
+			}
+		}
+	}
+}
+
+//calls span(x, y, length, coverage) for every covered run of pixels between from and to
+//coverage is the exact covered width on each sample row, so shapes sharing an edge add up to exactly the coverage of both together
+pub fn rasterize<O: Outline + ?Sized, F: FnMut(i32, i32, i32, f32)>(outline: &O, rule: FillRule, from: Vector2, to: Vector2, mut span: F) -> Result<(), RasterError> {
+	let mut edges = Edges::new();
+	let (mut y, mut rows) = (from.y, to.y - from.y);
+
+	while y < to.y {
+		rows = rows.min(to.y - y);
+
+		if edges.collect(outline, Region::new(vec2(from.x, y), vec2(to.x, y + rows))) {
+			edges.fill(rule, &mut span);
+			y += rows;
+		} else if rows > 1 {
+			rows /= 2;
+		} else {
+			//a single row with too many edges is split along x, carrying the winding of what is left of each piece
+			let (mut x, mut cols) = (from.x, to.x - from.x);
+
+			while x < to.x {
+				cols = cols.min(to.x - x);
+
+				if edges.collect(outline, Region::new(vec2(x, y), vec2(x + cols, y + 1))) {
+					edges.fill(rule, &mut span);
+					x += cols;
+				} else if cols > 1 {
+					cols /= 2;
+				} else {
+					return Err(RasterError::TooManyEdges);
+				}
+			}
+
+			y += 1;
+		}
+
+		//nothing outside the outline needs walking again
+		let bounds = edges.bounds;
+		if bounds.is_empty() || y >= bounds.to.y {
+			break;
+		}
+
+		y = y.max(bounds.from.y);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct Points<'a>(&'a [Vector2f]);
+
+	impl<'a> Outline for Points<'a> {
+		fn edges<F: FnMut(Vector2f, Vector2f)>(&self, mut f: F) {
+			let mut prev = self.0[self.0.len() - 1];
+			for &p in self.0 {
+				f(prev, p);
+				prev = p;
+			}
+		}
+	}
+
+	//coverage of each pixel of a 16x16 area, added up over every outline
+	fn cover<O: Outline + ?Sized>(acc: &mut [[f32; 16]; 16], outline: &O, rule: FillRule) -> Result<(), RasterError> {
+		rasterize(outline, rule, vec2(0, 0), vec2(16, 16), |x, y, len, cov| {
+			for x in x..x+len {
+				acc[y as usize][x as usize] += cov;
+			}
+		})
+	}
+
+	fn total(acc: &[[f32; 16]; 16]) -> f32 {
+		acc.iter().flat_map(|row| row.iter()).sum()
+	}
+
+	#[test]
+	fn coverage_adds_up_to_area() {
+		//corners on whole pixels and edges crossing every sample row on a fixed point step, so nothing is rounded
+		let mut acc = [[0.0; 16]; 16];
+		cover(&mut acc, &Points(&[vec2f(2.0, 2.0), vec2f(14.0, 5.0), vec2f(5.0, 14.0)]), FillRule::NonZero).unwrap();
+
+		assert_eq!(total(&acc), 0.5 * (12.0 * 12.0 - 3.0 * 3.0));
+		assert!(acc.iter().flat_map(|row| row.iter()).all(|&c| c <= 1.0));
+	}
+
+	#[test]
+	fn shared_edges_add_up_to_the_whole() {
+		let (a, b, c, d) = (vec2f(1.3, 0.7), vec2f(14.6, 2.2), vec2f(12.1, 15.4), vec2f(2.8, 11.9));
+
+		let mut halves = [[0.0; 16]; 16];
+		cover(&mut halves, &Points(&[a, b, c]), FillRule::NonZero).unwrap();
+		cover(&mut halves, &Points(&[a, c, d]), FillRule::NonZero).unwrap();
+
+		let mut whole = [[0.0; 16]; 16];
+		cover(&mut whole, &Points(&[a, b, c, d]), FillRule::NonZero).unwrap();
+
+		assert_eq!(halves, whole);
+	}
+
+	#[test]
+	fn top_left_rule() {
+		//2.125 is the first sample row of pixel row 2, which only the shape below it gets
+		let mut above = [[0.0; 16]; 16];
+		cover(&mut above, &Points(&[vec2f(0.0, 0.0), vec2f(4.0, 0.0), vec2f(4.0, 2.125), vec2f(0.0, 2.125)]), FillRule::NonZero).unwrap();
+
+		let mut below = [[0.0; 16]; 16];
+		cover(&mut below, &Points(&[vec2f(0.0, 2.125), vec2f(4.0, 2.125), vec2f(4.0, 6.0), vec2f(0.0, 6.0)]), FillRule::NonZero).unwrap();
+
+		assert_eq!(above[2][0], 0.0);
+		assert_eq!(below[2][0], 1.0);
+
+		//edges on pixel boundaries fill whole pixels and nothing next to them
+		assert_eq!(above[1][3], 1.0);
+		assert_eq!(above[1][4], 0.0);
+		assert_eq!(total(&above) + total(&below), 24.0);
+	}
+
+	#[test]
+	fn fill_rules() {
+		//a square wound twice, once inside the other
+		let square = |from: f32, to: f32| [vec2f(from, from), vec2f(to, from), vec2f(to, to), vec2f(from, to)];
+		let (outer, inner) = (square(2.0, 14.0), square(5.0, 11.0));
+		let both = [outer[0], outer[1], outer[2], outer[3], outer[0], inner[0], inner[1], inner[2], inner[3], inner[0]];
+
+		let mut non_zero = [[0.0; 16]; 16];
+		cover(&mut non_zero, &Points(&both), FillRule::NonZero).unwrap();
+
+		let mut even_odd = [[0.0; 16]; 16];
+		cover(&mut even_odd, &Points(&both), FillRule::EvenOdd).unwrap();
+
+		assert_eq!(total(&non_zero), 144.0);
+		assert_eq!(total(&even_odd), 144.0 - 36.0);
+	}
+
+	#[test]
+	fn many_edges_are_filled_in_pieces() {
+		//a saw with more teeth than MAX_EDGES along the same rows, on top of a rectangle
+		let teeth = MAX_EDGES;
+		let mut points = [vec2f(0.0, 0.0); 2*MAX_EDGES + 3];
+
+		for i in 0..teeth {
+			let x = i as f32 * 16.0 / teeth as f32;
+			points[2*i] = vec2f(x, 4.0);
+			points[2*i + 1] = vec2f(x + 8.0 / teeth as f32, 2.0);
+		}
+
+		points[2*teeth] = vec2f(16.0, 4.0);
+		points[2*teeth + 1] = vec2f(16.0, 8.0);
+		points[2*teeth + 2] = vec2f(0.0, 8.0);
+
+		let mut acc = [[0.0; 16]; 16];
+		cover(&mut acc, &Points(&points), FillRule::NonZero).unwrap();
+
+		assert_eq!(total(&acc), 16.0 * 4.0 + 16.0 * 2.0 / 2.0);
+		assert_eq!(acc[5], [1.0; 16]);
+	}
+
+	#[test]
+	fn too_many_edges_through_a_pixel() {
+		//a zigzag inside pixel (8, 8)
+		let mut points = [vec2f(0.0, 0.0); MAX_EDGES + 2];
+		for (i, p) in points.iter_mut().enumerate() {
+			*p = vec2f(8.1 + i as f32 * 0.8 / (MAX_EDGES + 2) as f32, if i % 2 == 0 { 8.1 } else { 8.9 });
+		}
+
+		let mut acc = [[0.0; 16]; 16];
+		assert_eq!(cover(&mut acc, &Points(&points), FillRule::NonZero), Err(RasterError::TooManyEdges));
+	}
+}
@@ -4,6 +4,7 @@ pub trait Bounded {
 	fn inside(&self, pos: Vector2) -> bool;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Region {
 	pub from: Vector2,
 	pub to: Vector2
@@ -13,6 +14,34 @@ impl Region {
 	pub fn new(from: Vector2, to: Vector2) -> Self {
 		Region {from, to}
 	}
+
+	//the part inside both, which can be empty
+	pub fn intersect(self, other: Region) -> Region {
+		Region {
+			from: vec2(self.from.x.max(other.from.x), self.from.y.max(other.from.y)),
+			to: vec2(self.to.x.min(other.to.x), self.to.y.min(other.to.y))
+		}
+	}
+
+	//the smallest region containing both
+	pub fn union(self, other: Region) -> Region {
+		Region {
+			from: vec2(self.from.x.min(other.from.x), self.from.y.min(other.from.y)),
+			to: vec2(self.to.x.max(other.to.x), self.to.y.max(other.to.y))
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.from.x >= self.to.x || self.from.y >= self.to.y
+	}
+
+	pub fn contains(&self, other: &Region) -> bool {
+		other.from.x >= self.from.x && other.from.y >= self.from.y && other.to.x <= self.to.x && other.to.y <= self.to.y
+	}
+
+	pub fn area(&self) -> i32 {
+		if self.is_empty() { 0 } else { (self.to.x - self.from.x) * (self.to.y - self.from.y) }
+	}
 }
 
 impl Bounded for Region {
@@ -0,0 +1,147 @@
+use super::*;
+use drawing::{ToPixel, Buffer, WriteBuffer, BlendMode};
+
+//a band of rows of a taller frame, drawn in frame coordinates
+//writes outside the band are dropped, and shapes are clipped to it
+pub struct Band<'a, B> {
+	pub draw: &'a mut B,
+	//first row of the frame in the band
+	pub top: i32,
+	pub frame_height: i32
+}
+
+impl<'a, B: Buffer> Band<'a, B> {
+	//rows of the band inside the frame, the last band can be cut short
+	pub fn rows(&self) -> i32 {
+		self.draw.height().min(self.frame_height - self.top)
+	}
+
+	//row y of the frame in draw, if it is in the band
+	fn row(&self, y: i32) -> Option<i32> {
+		let y = y - self.top;
+		if y >= 0 && y < self.draw.height() { Some(y) } else { None }
+	}
+}
+
+impl<'a, B: Buffer> Buffer for Band<'a, B> {
+	type Format = B::Format;
+
+	fn width(&self) -> i32 {
+		self.draw.width()
+	}
+
+	fn height(&self) -> i32 {
+		self.frame_height
+	}
+
+	//rows outside the band read from its nearest edge
+	fn get_pixel(&self, x: i32, y: i32) -> B::Format {
+		let y = (y - self.top).max(0).min(self.draw.height() - 1);
+		self.draw.get_pixel(x, y)
+	}
+}
+
+impl<'a, B: WriteBuffer> WriteBuffer for Band<'a, B> {
+	fn set_pixel(&mut self, x: i32, y: i32, p: B::Format) {
+		if let Some(y) = self.row(y) {
+			self.draw.set_pixel(x, y, p);
+		}
+	}
+
+	fn blend_mode(&self) -> BlendMode {
+		self.draw.blend_mode()
+	}
+
+	fn clip(&self) -> Region {
+		let clip = self.draw.clip();
+		Region::new(vec2(clip.from.x, clip.from.y + self.top), vec2(clip.to.x, self.top + self.rows().min(clip.to.y)))
+	}
+
+	fn fill_span(&mut self, x: i32, y: i32, len: i32, p: B::Format) {
+		if let Some(y) = self.row(y) {
+			self.draw.fill_span(x, y, len, p);
+		}
+	}
+
+	fn blend_span(&mut self, x: i32, y: i32, len: i32, p: B::Format, alpha: f32) {
+		if let Some(y) = self.row(y) {
+			self.draw.blend_span(x, y, len, p, alpha);
+		}
+	}
+
+	fn copy_row<P: ToPixel<B::Format>>(&mut self, x: i32, y: i32, pixels: &[P]) {
+		if let Some(y) = self.row(y) {
+			self.draw.copy_row(x, y, pixels);
+		}
+	}
+}
+
+//renders a frame of frame_height rows through band, a buffer only a few rows tall
+//for each band the buffer is cleared to background, draw is called with the band, then flush(top, rows, band) sends it to the display
+//draw has to draw the whole frame every time, anything outside the band is skipped
+pub fn render_strips<B, D, F>(band: &mut B, frame_height: i32, background: B::Format, mut draw: D, mut flush: F)
+	where B: WriteBuffer, D: FnMut(&mut Band<B>), F: FnMut(i32, i32, &B) {
+	let band_height = band.height();
+	if band_height <= 0 {
+		return;
+	}
+
+	let mut top = 0;
+	while top < frame_height {
+		for y in 0..band_height {
+			band.fill_span(0, y, band.width(), background.clone());
+		}
+
+		let rows = {
+			let mut b = Band {draw: &mut *band, top, frame_height};
+			draw(&mut b);
+
+			b.rows()
+		};
+
+		flush(top, rows, band);
+		top += band_height;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use drawing::{Drawing, FrameBuffer, RGB, Filter};
+	use path::StrokeStyle;
+	use raster::FillRule;
+
+	fn scene<B: Buffer<Format=RGB> + WriteBuffer>(b: &mut B) {
+		let image: FrameBuffer<RGB, 4, 4> = FrameBuffer::new(RGB(0, 200, 100));
+
+		b.circle(vec2(12, 10), 9, &RGB(255, 0, 0));
+		b.triangle([vec2(2, 28), vec2(30, 3), vec2(26, 27)], &RGB(0, 0, 255));
+		b.line(vec2(0, 0), vec2(31, 29), &RGB(255, 255, 255), &StrokeStyle::new(2.5));
+		b.poly(&[vec2(4, 4), vec2(20, 12), vec2(6, 26)], FillRule::EvenOdd, &RGB(90, 90, 0)).unwrap();
+		b.copy_transform(vec2(16, 16), vec2f(3.0, 2.0), vec2(2, 2), 0.6, vec2f(0.0, 0.0), &image, Filter::Bilinear);
+	}
+
+	#[test]
+	fn bands_add_up_to_the_frame() {
+		let mut frame: FrameBuffer<RGB, 32, 30> = FrameBuffer::new(RGB(10, 10, 10));
+		scene(&mut frame);
+
+		//30 rows in bands of 8, so the last one only fills 6
+		let mut band: FrameBuffer<RGB, 32, 8> = FrameBuffer::new(RGB(0, 0, 0));
+		let mut strips: FrameBuffer<RGB, 32, 30> = FrameBuffer::new(RGB(0, 0, 0));
+		let mut flushed = [(0, 0); 4];
+		let mut count = 0;
+
+		render_strips(&mut band, 30, RGB(10, 10, 10), |b| scene(b), |top, rows, band| {
+			for y in 0..rows {
+				strips.pixels[(top + y) as usize] = band.pixels[y as usize];
+			}
+
+			flushed[count] = (top, rows);
+			count += 1;
+		});
+
+		assert_eq!(flushed, [(0, 8), (8, 8), (16, 8), (24, 6)]);
+		assert!(strips.pixels[..] == frame.pixels[..]);
+	}
+}